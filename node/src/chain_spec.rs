@@ -1,8 +1,10 @@
 use sp_core::{Pair, Public, sr25519};
 use node_template_runtime::{
 	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig,
-	SudoConfig, SystemConfig, WASM_BINARY, Signature
+	SudoConfig, SystemConfig, WASM_BINARY, Signature, Hash,
+	CollectionModule, CollectionModuleConfig, NFTModuleConfig, ExchangeModuleConfig, NFTDAOModuleConfig,
 };
+use node_template_runtime::pallet_collection::CollectionSeed;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_finality_grandpa::AuthorityId as GrandpaId;
 use sp_runtime::traits::{Verify, IdentifyAccount};
@@ -125,6 +127,11 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 	))
 }
 
+/// The id of a demo collection created at genesis.
+pub fn demo_collection_id(owner: &AccountId, salt: &[u8]) -> Hash {
+	CollectionModule::deterministic_collection_id(owner, &CollectionSeed::Salt(salt.to_vec()))
+}
+
 /// Configure initial storage state for FRAME modules.
 fn testnet_genesis(
 	wasm_binary: &[u8],
@@ -133,6 +140,10 @@ fn testnet_genesis(
 	endowed_accounts: Vec<AccountId>,
 	_enable_println: bool,
 ) -> GenesisConfig {
+	let bob = get_account_id_from_seed::<sr25519::Public>("Bob");
+	let nft_collection_id = demo_collection_id(&root_key, b"demo-nft");
+	let ft_collection_id = demo_collection_id(&root_key, b"demo-ft");
+
 	GenesisConfig {
 		frame_system: Some(SystemConfig {
 			// Add Wasm runtime to storage.
@@ -151,7 +162,35 @@ fn testnet_genesis(
 		}),
 		pallet_sudo: Some(SudoConfig {
 			// Assign network admin rights.
-			key: root_key,
+			key: root_key.clone(),
+		}),
+		// Demo collections, tokens, an order and a DAO so that a new chain has some state to work with.
+		pallet_collection: Some(CollectionModuleConfig {
+			collections: vec![
+				(root_key.clone(), b"Starry demo NFT".to_vec(), false, b"demo-nft".to_vec()),
+				(root_key.clone(), b"Starry demo FT".to_vec(), true, b"demo-ft".to_vec()),
+			],
+		}),
+		pallet_nft: Some(NFTModuleConfig {
+			non_fungible_tokens: vec![
+				(nft_collection_id, root_key.clone(), b"Starry demo NFT #1".to_vec(), 10),
+				(nft_collection_id, bob.clone(), b"Starry demo NFT #2".to_vec(), 5),
+			],
+			fungible_tokens: vec![
+				(ft_collection_id, root_key.clone(), 1_000_000),
+				(ft_collection_id, bob, 1_000_000),
+			],
+		}),
+		pallet_exchange: Some(ExchangeModuleConfig {
+			// Alice sells the first 3 NFTs of her batch.
+			nft_orders: vec![
+				(root_key.clone(), nft_collection_id, 0, 3, 10_000_000_000),
+			],
+		}),
+		pallet_nftdao: Some(NFTDAOModuleConfig {
+			daos: vec![
+				(root_key, b"Starry demo DAO".to_vec(), 10, 5, 5, 100, 10_000_000_000, 1_000_000_000, 3),
+			],
 		}),
 	}
 }
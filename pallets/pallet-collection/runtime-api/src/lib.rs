@@ -0,0 +1,19 @@
+//! Runtime API definition for the collection pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use pallet_collection::FungibleMetadata;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+    pub trait CollectionApi<AccountId, Hash> where
+        AccountId: Codec,
+        Hash: Codec,
+    {
+        /// Get the collection_id of the next deterministic collection created by `who`.
+        fn next_collection_id(who: AccountId, salt: Option<Vec<u8>>) -> Hash;
+        /// Get name, symbol and decimals of a FT collection.
+        fn fungible_metadata(collection_id: Hash) -> Option<FungibleMetadata>;
+    }
+}
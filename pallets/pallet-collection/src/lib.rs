@@ -11,6 +11,17 @@
 //! ### Dispatchable Functions
 //!
 //! * `create_collection` - Create a collection to represent NFT/FT.
+//! * `create_deterministic_collection` - Create a collection whose id is derived from the creator
+//! and a creator-local nonce or salt, so it can be known before the block is included.
+//! * `create_fungible_collection` - Create a FT collection with name, symbol and decimals.
+//! * `set_fungible_metadata` - Set name, symbol and decimals of a FT collection before the first mint.
+//! * `set_transferable` - Make the tokens of a collection transferable or soulbound before the first mint.
+//!
+//! A deposit of `CollectionDeposit` is reserved from the creator when a collection is created
+//! by `create_collection`, and it is returned when the collection is destroyed.
+//!
+//! Collections can also be created in the chain spec with the `collections` genesis config, their
+//! ids are derived from the owner and a salt like `create_deterministic_collection`.
 //!
 //! [`Call`]: ./enum.Call.html
 //! [`Config`]: ./trait.Config.html
@@ -22,7 +33,9 @@ use codec::{Decode, Encode};
 use frame_support::{
     decl_error, decl_event, decl_module, decl_storage,
     dispatch::{DispatchError, DispatchResult},
-    traits::Randomness,
+    ensure,
+    traits::{Currency, Get, Randomness, ReservableCurrency},
+    weights::Weight,
 };
 use frame_system::ensure_signed;
 use sp_runtime::{traits::Hash, ModuleId};
@@ -36,6 +49,7 @@ mod tests;
 
 /// Used to indicate the type of tokens in the Collection.
 #[derive(Encode, Decode, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
 pub enum TokenType {
     /// NFT type
     NonFungible,
@@ -43,6 +57,15 @@ pub enum TokenType {
     Fungible,
 }
 
+/// Used to derive the id of a deterministic collection.
+#[derive(Encode, Decode, Clone, PartialEq)]
+pub enum CollectionSeed {
+    /// The creator-local nonce.
+    Nonce(u128),
+    /// A salt chosen by the creator.
+    Salt(Vec<u8>),
+}
+
 /// Details of a collection.
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
 pub struct CollectionInfo<AccountId> {
@@ -50,21 +73,77 @@ pub struct CollectionInfo<AccountId> {
     pub uri: Vec<u8>,
     pub total_supply: u128,
     pub token_type: Option<TokenType>,
+    /// Tokens of a non-transferable (soulbound) collection can only be minted and burned.
+    pub transferable: bool,
+}
+
+/// Layout of `CollectionInfo` before storage version 1.
+#[derive(Encode, Decode)]
+pub(crate) struct OldCollectionInfo<AccountId> {
+    pub owner: AccountId,
+    pub uri: Vec<u8>,
+    pub total_supply: u128,
+    pub token_type: Option<TokenType>,
+}
+
+/// On-chain metadata of a FT collection.
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct FungibleMetadata {
+    pub name: Vec<u8>,
+    pub symbol: Vec<u8>,
+    pub decimals: u8,
 }
 
 const PALLET_ID: ModuleId = ModuleId(*b"Collecti");
 
+type BalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
 pub trait Config: frame_system::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
     type RandomnessSource: Randomness<Self::Hash>;
+    /// The currency used to reserve the collection deposit.
+    type Currency: ReservableCurrency<Self::AccountId>;
+    /// The amount reserved from the creator when a collection is created.
+    type CollectionDeposit: Get<BalanceOf<Self>>;
+    /// The maximum length of name and symbol of FT collection.
+    type StringLimit: Get<u32>;
 }
 
 decl_storage! {
     trait Store for Module<T: Config> as TemplateModule {
         /// Increment number used to create collection_id.
         pub Nonce get(fn get_nonce): u128;
+        /// Increment number of each creator used to create deterministic collection_id.
+        pub AccountNonces get(fn account_nonce): map hasher(blake2_128_concat) T::AccountId => u128;
         /// The set of collection.
         pub Collections get(fn collections): map hasher(blake2_128_concat) T::Hash => CollectionInfo<T::AccountId>;
+        /// The deposit reserved from the owner of the collection. collection_id => deposit
+        pub Deposits get(fn deposits): map hasher(blake2_128_concat) T::Hash => BalanceOf<T>;
+        /// Name, symbol and decimals of FT collections. collection_id => metadata
+        pub Metadata get(fn fungible_metadata): map hasher(blake2_128_concat) T::Hash => Option<FungibleMetadata>;
+        /// Whether tokens have ever been minted in the collection. collection_id => minted
+        pub MintedCollections get(fn is_minted): map hasher(blake2_128_concat) T::Hash => bool;
+        /// The version of storage, used by migrations.
+        pub StorageVersion get(fn storage_version): u32;
+    }
+    add_extra_genesis {
+        /// Collections created at genesis without deposit. (owner, uri, is_fungible, salt)
+        ///
+        /// The collection_id is derived like `create_deterministic_collection` with the salt.
+        config(collections): Vec<(T::AccountId, Vec<u8>, bool, Vec<u8>)>;
+        build(|config: &GenesisConfig<T>| {
+            for (owner, uri, is_fungible, salt) in &config.collections {
+                let collection_id = Module::<T>::deterministic_collection_id(owner, &CollectionSeed::Salt(salt.clone()));
+                assert!(!Collections::<T>::contains_key(collection_id), "Collection already exists in genesis");
+
+                Module::<T>::insert_collection(collection_id, owner.clone(), uri.clone(), *is_fungible);
+            }
+
+            // New chains don't need the migration to storage version 1.
+            StorageVersion::put(1);
+        });
     }
 }
 
@@ -77,6 +156,10 @@ decl_event!(
     {
         /// A collection was created. \[who, collection_id\]
         CollectionCreated(AccountId, Hash),
+        /// The metadata of a FT collection was set. \[collection_id, name, symbol, decimals\]
+        FungibleMetadataSet(Hash, Vec<u8>, Vec<u8>, u8),
+        /// Whether the tokens of a collection are transferable was changed. \[collection_id, transferable\]
+        TransferableSet(Hash, bool),
     }
 );
 
@@ -85,6 +168,20 @@ decl_error! {
     pub enum Error for Module<T: Config> {
         /// Nonce is too large to cause overflow.
         NumOverflow,
+        /// A collection with the same id already exists.
+        CollectionExisted,
+        /// Collection does not exist.
+        CollectionNotFound,
+        /// No permission to perform this operation.
+        PermissionDenied,
+        /// Only FT collections have metadata.
+        WrongTokenType,
+        /// Name or symbol is too long.
+        BadMetadata,
+        /// Metadata can't be changed after tokens were minted.
+        MetadataFrozen,
+        /// The collection already has minted tokens.
+        AlreadyMinted,
     }
 }
 
@@ -96,6 +193,14 @@ decl_module! {
         // Used for handling module events.
         fn deposit_event() = default;
 
+        fn on_runtime_upgrade() -> Weight {
+            if Self::storage_version() == 0 {
+                Self::migrate_to_v1()
+            } else {
+                0
+            }
+        }
+
         /// Create a new collection.
         ///
         /// The dispatch origin of this call must be _Signed_.
@@ -106,12 +211,207 @@ decl_module! {
         #[weight = 10_000]
         pub fn create_collection(origin, uri: Vec<u8>, is_fungible: bool) -> DispatchResult  {
             let who = ensure_signed(origin)?;
+
+            let deposit = T::CollectionDeposit::get();
+            T::Currency::reserve(&who, deposit)?;
+
             let collection_id = Self::_create_collection(who.clone(), uri, is_fungible)?;
+            Deposits::<T>::insert(collection_id, deposit);
+
+            Self::deposit_event(RawEvent::CollectionCreated(who, collection_id));
+
+            Ok(())
+        }
+
+        /// Create a new collection with a deterministic collection_id.
+        ///
+        /// The collection_id is derived from the creator and the salt, or the creator's
+        /// next nonce if there is no salt, so it can be calculated by `next_collection_id`
+        /// before the block is included.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        ///
+        /// Parameters:
+        /// - `uri`: Used to get the detailed information of the collection such as name, description, cover_image, which can be the CID of ipfs or a URL.
+        /// - `is_fungible`: Is FT or not.
+        /// - `salt`: Used to derive the collection_id instead of the creator's nonce.
+        #[weight = 10_000]
+        pub fn create_deterministic_collection(origin, uri: Vec<u8>, is_fungible: bool, salt: Option<Vec<u8>>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let seed = match salt {
+                Some(salt) => CollectionSeed::Salt(salt),
+                None => {
+                    let nonce = Self::account_nonce(&who).checked_add(1).ok_or(Error::<T>::NumOverflow)?;
+                    CollectionSeed::Nonce(nonce)
+                }
+            };
+
+            let collection_id = Self::deterministic_collection_id(&who, &seed);
+            ensure!(!Collections::<T>::contains_key(collection_id), Error::<T>::CollectionExisted);
+
+            let deposit = T::CollectionDeposit::get();
+            T::Currency::reserve(&who, deposit)?;
+
+            if let CollectionSeed::Nonce(nonce) = seed {
+                AccountNonces::<T>::insert(&who, nonce);
+            }
+
+            Self::insert_collection(collection_id, who.clone(), uri, is_fungible);
+            Deposits::<T>::insert(collection_id, deposit);
 
             Self::deposit_event(RawEvent::CollectionCreated(who, collection_id));
 
             Ok(())
         }
+
+        /// Create a new FT collection with metadata.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        ///
+        /// Parameters:
+        /// - `uri`: Used to get the detailed information of the collection such as name, description, cover_image, which can be the CID of ipfs or a URL.
+        /// - `name`: The name of FT.
+        /// - `symbol`: The symbol of FT.
+        /// - `decimals`: The number of decimals used to display balances.
+        #[weight = 10_000]
+        pub fn create_fungible_collection(origin, uri: Vec<u8>, name: Vec<u8>, symbol: Vec<u8>, decimals: u8) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let metadata = Self::check_metadata(name, symbol, decimals)?;
+
+            let deposit = T::CollectionDeposit::get();
+            T::Currency::reserve(&who, deposit)?;
+
+            let collection_id = Self::_create_collection(who.clone(), uri, true)?;
+            Deposits::<T>::insert(collection_id, deposit);
+            Metadata::<T>::insert(collection_id, &metadata);
+
+            Self::deposit_event(RawEvent::CollectionCreated(who, collection_id));
+            Self::deposit_event(RawEvent::FungibleMetadataSet(collection_id, metadata.name, metadata.symbol, metadata.decimals));
+
+            Ok(())
+        }
+
+        /// Set name, symbol and decimals of a FT collection.
+        ///
+        /// Metadata is frozen once tokens were minted in the collection.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the owner of the collection.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The FT collection.
+        /// - `name`: The name of FT.
+        /// - `symbol`: The symbol of FT.
+        /// - `decimals`: The number of decimals used to display balances.
+        #[weight = 10_000]
+        pub fn set_fungible_metadata(origin, collection_id: T::Hash, name: Vec<u8>, symbol: Vec<u8>, decimals: u8) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(Collections::<T>::contains_key(collection_id), Error::<T>::CollectionNotFound);
+
+            let collection = Self::collections(collection_id);
+            ensure!(collection.owner == who, Error::<T>::PermissionDenied);
+            ensure!(collection.token_type == Some(TokenType::Fungible), Error::<T>::WrongTokenType);
+            ensure!(!Self::is_minted(collection_id), Error::<T>::MetadataFrozen);
+
+            let metadata = Self::check_metadata(name, symbol, decimals)?;
+            Metadata::<T>::insert(collection_id, &metadata);
+
+            Self::deposit_event(RawEvent::FungibleMetadataSet(collection_id, metadata.name, metadata.symbol, metadata.decimals));
+
+            Ok(())
+        }
+
+        /// Set whether the tokens of a collection can be transferred.
+        ///
+        /// It can only be changed before tokens were minted in the collection.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the owner of the collection.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The collection.
+        /// - `transferable`: false makes the tokens soulbound.
+        #[weight = 10_000]
+        pub fn set_transferable(origin, collection_id: T::Hash, transferable: bool) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(Collections::<T>::contains_key(collection_id), Error::<T>::CollectionNotFound);
+
+            let collection = Self::collections(collection_id);
+            ensure!(collection.owner == who, Error::<T>::PermissionDenied);
+            ensure!(!Self::is_minted(collection_id), Error::<T>::AlreadyMinted);
+
+            Collections::<T>::insert(collection_id, CollectionInfo {
+                transferable,
+                ..collection
+            });
+
+            Self::deposit_event(RawEvent::TransferableSet(collection_id, transferable));
+
+            Ok(())
+        }
+    }
+}
+
+impl<T: Config> Module<T> {
+    /// Derive a collection_id from the creator and a seed.
+    pub fn deterministic_collection_id(who: &T::AccountId, seed: &CollectionSeed) -> T::Hash {
+        T::Hashing::hash(&(PALLET_ID, who, seed).encode())
+    }
+
+    /// The collection_id of the next deterministic collection created by `who`.
+    pub fn next_collection_id(who: &T::AccountId, salt: Option<Vec<u8>>) -> T::Hash {
+        let seed = match salt {
+            Some(salt) => CollectionSeed::Salt(salt),
+            None => CollectionSeed::Nonce(Self::account_nonce(who).saturating_add(1)),
+        };
+
+        Self::deterministic_collection_id(who, &seed)
+    }
+
+    fn check_metadata(name: Vec<u8>, symbol: Vec<u8>, decimals: u8) -> Result<FungibleMetadata, DispatchError> {
+        let limit = T::StringLimit::get() as usize;
+        ensure!(name.len() <= limit && symbol.len() <= limit, Error::<T>::BadMetadata);
+
+        Ok(FungibleMetadata { name, symbol, decimals })
+    }
+
+    fn insert_collection(collection_id: T::Hash, who: T::AccountId, uri: Vec<u8>, is_fungible: bool) {
+        let token_type = if is_fungible {
+            Some(TokenType::Fungible)
+        } else {
+            Some(TokenType::NonFungible)
+        };
+
+        let collection = CollectionInfo {
+            owner: who,
+            total_supply: 0,
+            uri,
+            token_type,
+            transferable: true,
+        };
+
+        Collections::<T>::insert(collection_id, collection);
+    }
+
+    /// Add `transferable` to existing collections, they were all transferable before.
+    pub(crate) fn migrate_to_v1() -> Weight {
+        let mut count: Weight = 0;
+        Collections::<T>::translate::<OldCollectionInfo<T::AccountId>, _>(|_, old| {
+            count = count.saturating_add(1);
+            Some(CollectionInfo {
+                owner: old.owner,
+                uri: old.uri,
+                total_supply: old.total_supply,
+                token_type: old.token_type,
+                transferable: true,
+            })
+        });
+
+        StorageVersion::put(1);
+
+        T::DbWeight::get().reads_writes(count, count.saturating_add(1))
     }
 }
 
@@ -120,6 +420,10 @@ pub trait CollectionInterface<Hash, AccountId> {
     fn collection_exist(collection_id: Hash) -> bool;
     /// Get a collection by collection_id.
     fn get_collection(collection_id: Hash) -> CollectionInfo<AccountId>;
+    /// Get the metadata of a FT collection.
+    fn get_fungible_metadata(collection_id: Hash) -> Option<FungibleMetadata>;
+    /// Whether tokens have ever been minted in the collection.
+    fn is_minted(collection_id: Hash) -> bool;
     /// Generate collection_id through nonce.
     fn generate_collection_id(nonce: u128) -> Result<Hash, DispatchError>;
     /// nonce plus one.
@@ -130,7 +434,7 @@ pub trait CollectionInterface<Hash, AccountId> {
         uri: Vec<u8>,
         is_fungible: bool,
     ) -> Result<Hash, DispatchError>;
-    /// destory a collection by collection_id.
+    /// destory a collection by collection_id and return the deposit to the owner.
     fn destory_collection(collection_id: &Hash);
     /// Increase a certain amount of of collection total_supply by collection_id.
     fn add_total_supply(collection_id: Hash, amount: u128) -> Result<u128, DispatchError>;
@@ -147,6 +451,14 @@ impl<T: Config> CollectionInterface<T::Hash, T::AccountId> for Module<T> {
         Self::collections(collection_id)
     }
 
+    fn get_fungible_metadata(collection_id: T::Hash) -> Option<FungibleMetadata> {
+        Self::fungible_metadata(collection_id)
+    }
+
+    fn is_minted(collection_id: T::Hash) -> bool {
+        Self::is_minted(collection_id)
+    }
+
     fn generate_collection_id(nonce: u128) -> Result<T::Hash, DispatchError> {
         let seed = T::RandomnessSource::random_seed();
         let collection_id = T::Hashing::hash(&(PALLET_ID, seed, nonce).encode());
@@ -172,26 +484,18 @@ impl<T: Config> CollectionInterface<T::Hash, T::AccountId> for Module<T> {
 
         let collection_id = Self::generate_collection_id(nonce)?;
 
-        let token_type = if is_fungible {
-            Some(TokenType::Fungible)
-        } else {
-            Some(TokenType::NonFungible)
-        };
-
-        let collection = CollectionInfo {
-            owner: who,
-            total_supply: 0,
-            uri,
-            token_type,
-        };
-
-        Collections::<T>::insert(collection_id, collection);
+        Self::insert_collection(collection_id, who, uri, is_fungible);
 
         Ok(collection_id)
     }
 
     fn destory_collection(collection_id: &T::Hash) {
-        Collections::<T>::remove(collection_id)
+        let collection = Collections::<T>::take(collection_id);
+        let deposit = Deposits::<T>::take(collection_id);
+        Metadata::<T>::remove(collection_id);
+        MintedCollections::<T>::remove(collection_id);
+
+        T::Currency::unreserve(&collection.owner, deposit);
     }
 
     fn add_total_supply(collection_id: T::Hash, amount: u128) -> Result<u128, DispatchError> {
@@ -208,6 +512,7 @@ impl<T: Config> CollectionInterface<T::Hash, T::AccountId> for Module<T> {
         };
 
         Collections::<T>::insert(collection_id, new_collection);
+        MintedCollections::<T>::insert(collection_id, true);
 
         Ok(total_supply)
     }
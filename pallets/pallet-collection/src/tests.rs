@@ -1,5 +1,5 @@
-use crate::{mock::*};
-use frame_support::assert_ok;
+use crate::{mock::*, Error};
+use frame_support::{assert_noop, assert_ok};
 use crate::CollectionInterface;
 
 
@@ -19,3 +19,207 @@ fn test_create_collection() {
         assert_eq!(collection.owner, alice_address);
     });
 }
+
+#[test]
+fn create_collection_reserves_deposit() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+
+        assert_ok!(TemplateModule::create_collection(alice, vec![2, 3, 3], false));
+
+        let nonce = TemplateModule::get_nonce();
+        let collection_id = <TemplateModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        assert_eq!(TemplateModule::deposits(collection_id), 10);
+        assert_eq!(Balances::reserved_balance(alice_address), 10);
+
+        <TemplateModule as CollectionInterface<_, _>>::destory_collection(&collection_id);
+
+        assert_eq!(TemplateModule::collection_exist(collection_id), false);
+        assert_eq!(Balances::reserved_balance(alice_address), 0);
+    });
+}
+
+#[test]
+fn create_collection_failed() {
+    new_test_ext().execute_with(|| {
+        let bob = Origin::signed(2);
+
+        assert_noop!(
+            TemplateModule::create_collection(bob, vec![2, 3, 3], false),
+            pallet_balances::Error::<Test, _>::InsufficientBalance
+        );
+    });
+}
+
+#[test]
+fn create_deterministic_collection_success() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+
+        let collection_id = TemplateModule::next_collection_id(&alice_address, None);
+        assert_ok!(TemplateModule::create_deterministic_collection(alice.clone(), vec![2, 3, 3], false, None));
+        assert_eq!(TemplateModule::account_nonce(alice_address), 1);
+        assert_eq!(TemplateModule::collections(collection_id).owner, alice_address);
+
+        let next_collection_id = TemplateModule::next_collection_id(&alice_address, None);
+        assert_ne!(collection_id, next_collection_id);
+
+        let salted_collection_id = TemplateModule::next_collection_id(&alice_address, Some(vec![1]));
+        assert_ok!(TemplateModule::create_deterministic_collection(alice, vec![2, 3, 3], true, Some(vec![1])));
+        assert_eq!(TemplateModule::account_nonce(alice_address), 1);
+        assert_eq!(TemplateModule::collections(salted_collection_id).owner, alice_address);
+    });
+}
+
+#[test]
+fn create_deterministic_collection_failed() {
+    new_test_ext().execute_with(|| {
+        let alice = Origin::signed(1);
+
+        assert_ok!(TemplateModule::create_deterministic_collection(alice.clone(), vec![2, 3, 3], false, Some(vec![1])));
+        assert_noop!(
+            TemplateModule::create_deterministic_collection(alice, vec![2, 3, 3], false, Some(vec![1])),
+            Error::<Test>::CollectionExisted
+        );
+    });
+}
+
+#[test]
+fn set_fungible_metadata_success() {
+    new_test_ext().execute_with(|| {
+        let alice = Origin::signed(1);
+
+        assert_ok!(TemplateModule::create_fungible_collection(alice.clone(), vec![2, 3, 3], b"Star".to_vec(), b"STR".to_vec(), 12));
+        let nonce = TemplateModule::get_nonce();
+        let collection_id = <TemplateModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        let metadata = TemplateModule::fungible_metadata(collection_id).unwrap();
+        assert_eq!(metadata.symbol, b"STR".to_vec());
+        assert_eq!(metadata.decimals, 12);
+
+        assert_ok!(TemplateModule::set_fungible_metadata(alice, collection_id, b"Starry".to_vec(), b"STRY".to_vec(), 18));
+        let metadata = TemplateModule::fungible_metadata(collection_id).unwrap();
+        assert_eq!(metadata.name, b"Starry".to_vec());
+        assert_eq!(metadata.decimals, 18);
+    });
+}
+
+#[test]
+fn set_fungible_metadata_failed() {
+    new_test_ext().execute_with(|| {
+        let alice = Origin::signed(1);
+        let bob = Origin::signed(2);
+
+        assert_noop!(
+            TemplateModule::create_fungible_collection(alice.clone(), vec![2, 3, 3], vec![0; 51], b"STR".to_vec(), 12),
+            Error::<Test>::BadMetadata
+        );
+
+        assert_ok!(TemplateModule::create_collection(alice.clone(), vec![2, 3, 3], false));
+        let nonce = TemplateModule::get_nonce();
+        let nft_collection_id = <TemplateModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        assert_noop!(
+            TemplateModule::set_fungible_metadata(alice.clone(), nft_collection_id, b"Star".to_vec(), b"STR".to_vec(), 12),
+            Error::<Test>::WrongTokenType
+        );
+
+        assert_ok!(TemplateModule::create_collection(alice.clone(), vec![2, 3, 3], true));
+        let nonce = TemplateModule::get_nonce();
+        let collection_id = <TemplateModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        assert_noop!(
+            TemplateModule::set_fungible_metadata(bob, collection_id, b"Star".to_vec(), b"STR".to_vec(), 12),
+            Error::<Test>::PermissionDenied
+        );
+
+        assert_ok!(<TemplateModule as CollectionInterface<_, _>>::add_total_supply(collection_id, 100));
+        assert_noop!(
+            TemplateModule::set_fungible_metadata(alice, collection_id, b"Star".to_vec(), b"STR".to_vec(), 12),
+            Error::<Test>::MetadataFrozen
+        );
+    });
+}
+
+#[test]
+fn set_transferable_success() {
+    new_test_ext().execute_with(|| {
+        let alice = Origin::signed(1);
+
+        assert_ok!(TemplateModule::create_collection(alice.clone(), vec![2, 3, 3], false));
+        let nonce = TemplateModule::get_nonce();
+        let collection_id = <TemplateModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        assert_eq!(TemplateModule::collections(collection_id).transferable, true);
+
+        assert_ok!(TemplateModule::set_transferable(alice, collection_id, false));
+        assert_eq!(TemplateModule::collections(collection_id).transferable, false);
+    });
+}
+
+#[test]
+fn set_transferable_failed() {
+    new_test_ext().execute_with(|| {
+        let alice = Origin::signed(1);
+        let bob = Origin::signed(2);
+
+        assert_ok!(TemplateModule::create_collection(alice.clone(), vec![2, 3, 3], false));
+        let nonce = TemplateModule::get_nonce();
+        let collection_id = <TemplateModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        assert_noop!(
+            TemplateModule::set_transferable(bob, collection_id, false),
+            Error::<Test>::PermissionDenied
+        );
+
+        assert_ok!(<TemplateModule as CollectionInterface<_, _>>::add_total_supply(collection_id, 1));
+        assert_noop!(
+            TemplateModule::set_transferable(alice, collection_id, false),
+            Error::<Test>::AlreadyMinted
+        );
+    });
+}
+
+#[test]
+fn genesis_collections_success() {
+    new_test_ext_with_collections(vec![(1, vec![2, 3, 3], true, vec![1])]).execute_with(|| {
+        let collection_id = TemplateModule::next_collection_id(&1, Some(vec![1]));
+        let collection = TemplateModule::collections(collection_id);
+        assert_eq!(collection.owner, 1);
+        assert!(collection.token_type == Some(crate::TokenType::Fungible));
+        assert_eq!(TemplateModule::deposits(collection_id), 0);
+    });
+}
+
+#[test]
+fn migrate_to_v1_keeps_collections_transferable() {
+    new_test_ext().execute_with(|| {
+        let collection_id = sp_core::H256::repeat_byte(1);
+        frame_support::storage::unhashed::put(
+            &crate::Collections::<Test>::hashed_key_for(collection_id),
+            &crate::OldCollectionInfo {
+                owner: 1u64,
+                uri: vec![2, 3, 3],
+                total_supply: 10,
+                token_type: Some(crate::TokenType::NonFungible),
+            },
+        );
+        assert_eq!(TemplateModule::storage_version(), 0);
+
+        TemplateModule::migrate_to_v1();
+
+        let collection = TemplateModule::collections(collection_id);
+        assert_eq!(collection.owner, 1);
+        assert_eq!(collection.uri, vec![2, 3, 3]);
+        assert_eq!(collection.total_supply, 10);
+        assert!(collection.token_type == Some(crate::TokenType::NonFungible));
+        assert!(collection.transferable);
+        assert_eq!(TemplateModule::storage_version(), 1);
+    });
+}
+
+#[test]
+fn genesis_sets_storage_version() {
+    new_test_ext_with_collections(vec![]).execute_with(|| {
+        assert_eq!(TemplateModule::storage_version(), 1);
+    });
+}
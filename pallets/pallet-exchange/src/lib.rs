@@ -28,13 +28,23 @@
 //! * `sell_semi_token` - Sell FTs to pool.
 //! * `withdraw_pool` - After the time of the pool has passed, the creator of the pool can obtain the assets in the pool.
 //!
+//! Orders and pools of a frozen seller or collection can't be bought, see `freeze_account` and
+//! `freeze_collection` of pallet-nft.
+//!
+//! An order keeps the hash of the NFTs and FTs linked in pallet-graph to its first NFT, the one every
+//! purchase takes, when it was listed. The buyer passes the subtree hash they expect, the purchase
+//! fails if it isn't the hash of the order or if the subtree changed since listing.
+//!
+//! NFT orders can be created in the chain spec with the `nft_orders` genesis config, it must be
+//! built after the genesis of pallet-nft.
+//!
 //! [`Call`]: ./enum.Call.html
 //! [`Config`]: ./trait.Config.html
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Decode, Encode};
-use frame_support::traits::{Currency, ExistenceRequirement::AllowDeath};
+use frame_support::traits::{Currency, ExistenceRequirement::AllowDeath, Get};
 /// Edit this file to define custom logic or remove it if it is not needed.
 /// Learn more about FRAME and the core library of Substrate FRAME pallets:
 /// https://substrate.dev/docs/en/knowledgebase/runtime/frame
@@ -42,10 +52,12 @@ use frame_support::{
     decl_error, decl_event, decl_module, decl_storage,
     dispatch::{DispatchError, DispatchResult},
     ensure,
+    weights::Weight,
 };
 use frame_system::{self as system, ensure_signed};
 use pallet_collection::{CollectionInterface, TokenType};
-use pallet_nft::NFTInterface;
+use pallet_graph::GraphInterface;
+use pallet_nft::{KeepsTokenRanges, NFTInterface};
 use sp_runtime::{
     traits::{AccountIdConversion, CheckedAdd, CheckedMul, CheckedSub, SaturatedConversion},
     ModuleId,
@@ -71,6 +83,18 @@ pub struct NonFungibleOrderInfo<Hash, AccountId, Balance> {
     pub seller: AccountId,
     pub price: Balance,
     pub amount: u128,
+    /// The hash of the subtree of the NFT at `start_idx` when it was listed.
+    pub subtree_hash: Option<Hash>,
+}
+
+/// Layout of `NonFungibleOrderInfo` before storage version 1.
+#[derive(Encode, Decode)]
+pub(crate) struct OldNonFungibleOrderInfo<Hash, AccountId, Balance> {
+    pub collection_id: Hash,
+    pub start_idx: u128,
+    pub seller: AccountId,
+    pub price: Balance,
+    pub amount: u128,
 }
 
 /// Pool details.
@@ -91,6 +115,7 @@ pub trait Config: frame_system::Config {
     type Currency: Currency<Self::AccountId>;
     type Collection: CollectionInterface<Self::Hash, Self::AccountId>;
     type NFT: NFTInterface<Self::Hash, Self::AccountId>;
+    type Graph: GraphInterface<Self::Hash, Self::AccountId>;
 }
 
 decl_storage! {
@@ -101,6 +126,21 @@ decl_storage! {
         NonFungibleOrders get(fn nft_order): map hasher(blake2_128_concat) u128 => NonFungibleOrderInfo<T::Hash, T::AccountId, BalanceOf<T>>;
         // The set of FTs pools. (collection id, seller_account) => pool
         SemiFungiblePools get (fn semi_fungible_pool): map hasher(blake2_128_concat) (T::Hash, T::AccountId) => SemiFungiblePoolInfo<T::AccountId, BalanceOf<T>, T::BlockNumber>;
+        /// The version of storage, used by migrations.
+        pub StorageVersion get(fn storage_version): u32;
+    }
+    add_extra_genesis {
+        /// NFT orders created at genesis, the NFTs must be minted at genesis. (seller, collection_id, token_id, amount, price)
+        config(nft_orders): Vec<(T::AccountId, T::Hash, u128, u128, BalanceOf<T>)>;
+        build(|config: &GenesisConfig<T>| {
+            for (seller, collection_id, token_id, amount, price) in &config.nft_orders {
+                Module::<T>::do_sell_nft(seller.clone(), *collection_id, *token_id, *amount, *price)
+                    .expect("Genesis NFT orders can be created");
+            }
+
+            // New chains don't need the migration to storage version 1.
+            StorageVersion::put(1);
+        });
     }
 }
 
@@ -161,6 +201,10 @@ decl_error! {
         ExpiredSoldTime,
         /// Not yet crossed the duration of the pool.
         CanNotWithdraw,
+        /// The collection or the tokens of the seller are frozen.
+        Frozen,
+        /// The NFTs or FTs linked to the NFT are not the ones the buyer expected.
+        SubtreeChanged,
     }
 }
 
@@ -170,6 +214,14 @@ decl_module! {
 
         fn deposit_event() = default;
 
+        fn on_runtime_upgrade() -> Weight {
+            if Self::storage_version() == 0 {
+                Self::migrate_to_v1()
+            } else {
+                0
+            }
+        }
+
         /// Sell one or a batch of NFTs.
         ///
         /// The dispatch origin of this call must be _Signed_.
@@ -183,27 +235,7 @@ decl_module! {
         pub fn sell_nft(origin, collection_id: T::Hash, token_id: u128, amount: u128, price: BalanceOf<T>) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
-            ensure!(T::NFT::token_exist(collection_id, token_id), Error::<T>::TokenNotFound);
-
-            let token = T::NFT::get_nft_token(collection_id, token_id);
-
-            ensure!(token.owner == who, Error::<T>::PermissionDenied);
-
-            let nft_order_id = Self::next_nft_order_id();
-            let next_nft_order_id = nft_order_id.checked_add(1).ok_or(Error::<T>::NumOverflow)?;
-
-            T::NFT::_transfer_non_fungible(who.clone(), Self::account_id(), collection_id, token_id, amount)?;
-
-            let order_info = NonFungibleOrderInfo {
-                collection_id,
-                start_idx: token_id,
-                seller: who.clone(),
-                price,
-                amount
-            };
-
-            NonFungibleOrders::<T>::insert(nft_order_id, order_info);
-            NextNonFungibleOrderId::put(next_nft_order_id);
+            let nft_order_id = Self::do_sell_nft(who.clone(), collection_id, token_id, amount, price)?;
 
             Self::deposit_event(RawEvent::NonFungibleOrderCreated(
                 who,
@@ -217,11 +249,15 @@ decl_module! {
         ///
         /// The dispatch origin of this call must be _Signed_.
         ///
+        /// The first NFT of the order is bought with its subtree, the subtree must not have changed
+        /// since listing and must have the hash the buyer expects.
+        ///
         /// Parameters:
         /// - `order_id`: The id of the order
         /// - `amount`: How many NFTs to buy.
+        /// - `expected_subtree_hash`: The `subtree_hash` of the order, `None` if nothing is linked to its first NFT.
         #[weight = 10_000]
-        pub fn buy_nft(origin, order_id: u128, amount: u128) -> DispatchResult {
+        pub fn buy_nft(origin, order_id: u128, amount: u128, expected_subtree_hash: Option<T::Hash>) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
             ensure!(amount >= 1, Error::<T>::AmountLessThanOne);
@@ -230,6 +266,13 @@ decl_module! {
             let order = Self::nft_order(order_id);
 
             ensure!(order.amount >= amount, Error::<T>::AmountTooLarge);
+            ensure!(!T::NFT::is_frozen(&order.collection_id, &order.seller), Error::<T>::Frozen);
+            // Every purchase takes the NFT at start_idx, the one that may be a parent NFT.
+            let subtree_hash = T::Graph::subtree_hash(&order.collection_id, order.start_idx)?;
+            ensure!(
+                subtree_hash == order.subtree_hash && subtree_hash == expected_subtree_hash,
+                Error::<T>::SubtreeChanged
+            );
 
             let price = &order.price;
             let b_amout = amount.saturated_into::<BalanceOf<T>>();
@@ -239,6 +282,20 @@ decl_module! {
             let collection_id = &order.collection_id;
             let token_id = &order.start_idx;
 
+            // The rest of the order starts at the next NFT, whose subtree is kept from now on.
+            let rest = if *left_amount == 0 {
+                None
+            } else {
+                let start_idx = token_id.checked_add(&amount).ok_or(Error::<T>::NumOverflow)?;
+                let subtree_hash = T::Graph::subtree_hash(collection_id, start_idx)?;
+                Some(NonFungibleOrderInfo {
+                    amount: *left_amount,
+                    start_idx,
+                    subtree_hash,
+                    ..order.clone()
+                })
+            };
+
             T::Currency::transfer(&who, &order.seller, cost, AllowDeath)?;
             T::NFT::_transfer_non_fungible(Self::account_id(), who.clone(), *collection_id, *token_id, amount)?;
 
@@ -246,16 +303,9 @@ decl_module! {
             // let start_idx = sended_token.end_idx.checked_add(1).ok_or(Error::<T>::NumOverflow)?;
 
             // if sold out, remove order
-            if *left_amount == 0 {
-                NonFungibleOrders::<T>::remove(order_id);
-            } else {
-                let start_idx = token_id.checked_add(&amount).ok_or(Error::<T>::NumOverflow)?;
-                let order = NonFungibleOrderInfo {
-                    amount: *left_amount,
-                    start_idx,
-                    ..order
-                };
-                NonFungibleOrders::<T>::insert(order_id, order);
+            match rest {
+                Some(order) => NonFungibleOrders::<T>::insert(order_id, order),
+                None => NonFungibleOrders::<T>::remove(order_id),
             }
 
             Self::deposit_event(RawEvent::NonFungibleSold(
@@ -367,6 +417,7 @@ decl_module! {
             let pool = Self::semi_fungible_pool((&collection_id, &seller));
 
             ensure!(amount <= pool.supply, Error::<T>::AmountTooLarge);
+            ensure!(!T::NFT::is_frozen(&collection_id, &seller), Error::<T>::Frozen);
 
             let block_number = <system::Pallet<T>>::block_number();
             ensure!(block_number <= pool.end_time, Error::<T>::ExpiredSoldTime);
@@ -500,12 +551,70 @@ decl_module! {
     }
 }
 
+impl<T: Config> KeepsTokenRanges<T::Hash, T::AccountId> for Module<T> {
+    /// NFTs for sale are referenced by the start_idx of orders.
+    fn keeps_range(_collection_id: &T::Hash, _start_idx: u128, owner: &T::AccountId) -> bool {
+        *owner == Self::account_id()
+    }
+}
+
 impl<T: Config> Module<T> {
+    /// Add `subtree_hash` to existing orders, from the subtree of their first NFT now. An order
+    /// whose subtree is too large to be hashed can't be bought, only cancelled.
+    pub(crate) fn migrate_to_v1() -> Weight {
+        let mut count: Weight = 0;
+        NonFungibleOrders::<T>::translate::<OldNonFungibleOrderInfo<T::Hash, T::AccountId, BalanceOf<T>>, _>(|_, old| {
+            count = count.saturating_add(1);
+            let subtree_hash = T::Graph::subtree_hash(&old.collection_id, old.start_idx).ok().flatten();
+            Some(NonFungibleOrderInfo {
+                collection_id: old.collection_id,
+                start_idx: old.start_idx,
+                seller: old.seller,
+                price: old.price,
+                amount: old.amount,
+                subtree_hash,
+            })
+        });
+
+        StorageVersion::put(1);
+
+        T::DbWeight::get().reads_writes(count, count.saturating_add(1))
+    }
+
     /// Account of this pallet.
     pub fn account_id() -> T::AccountId {
         PALLET_ID.into_account()
     }
 
+    /// Escrow NFTs of `who` in the pallet and create an order, return the order_id.
+    fn do_sell_nft(who: T::AccountId, collection_id: T::Hash, token_id: u128, amount: u128, price: BalanceOf<T>) -> Result<u128, DispatchError> {
+        ensure!(T::NFT::token_exist(collection_id, token_id), Error::<T>::TokenNotFound);
+
+        let token = T::NFT::get_nft_token(collection_id, token_id);
+
+        ensure!(token.owner == who, Error::<T>::PermissionDenied);
+
+        let nft_order_id = Self::next_nft_order_id();
+        let next_nft_order_id = nft_order_id.checked_add(1).ok_or(Error::<T>::NumOverflow)?;
+        let subtree_hash = T::Graph::subtree_hash(&collection_id, token_id)?;
+
+        T::NFT::_transfer_non_fungible(who.clone(), Self::account_id(), collection_id, token_id, amount)?;
+
+        let order_info = NonFungibleOrderInfo {
+            collection_id,
+            start_idx: token_id,
+            seller: who,
+            price,
+            amount,
+            subtree_hash,
+        };
+
+        NonFungibleOrders::<T>::insert(nft_order_id, order_info);
+        NextNonFungibleOrderId::put(next_nft_order_id);
+
+        Ok(nft_order_id)
+    }
+
     /// pow operation
     /// r  = reserve_ratio / max_weight, max_weight = 1000000, reserve_ratio >= 1
     /// p = b * ((k / s + 1) ^ (n + 1) - 1)
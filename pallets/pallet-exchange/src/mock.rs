@@ -22,6 +22,7 @@ frame_support::construct_runtime!(
         TemplateModule: pallet_template::{Module, Call, Storage, Event<T>},
         CollectionModule: pallet_collection::{Module, Call, Storage, Event<T>},
         NFTModule: pallet_nft::{Module, Call, Storage, Event<T>},
+        GraphModule: pallet_graph::{Module, Call, Storage, Event<T>},
     }
 );
 
@@ -57,6 +58,13 @@ impl system::Config for Test {
 
 parameter_types! {
     pub const ExistentialDeposit: u64 = 1;
+    pub const CollectionDeposit: u64 = 0;
+    pub const StringLimit: u32 = 50;
+    pub const MaxBatchTransfers: u32 = 10;
+    pub const MaxEquippables: u32 = 2;
+    pub const MaxDepth: u32 = 6;
+    pub const MaxDescendants: u32 = 5;
+    pub const MaxTokenLocks: u32 = 3;
 }
 
 impl pallet_balances::Config for Test {
@@ -72,11 +80,24 @@ impl pallet_balances::Config for Test {
 impl pallet_collection::Config for Test {
     type Event = Event;
     type RandomnessSource = TestRandomness;
+    type Currency = Balances;
+    type CollectionDeposit = CollectionDeposit;
+    type StringLimit = StringLimit;
 }
 
 impl pallet_nft::Config for Test {
     type Event = Event;
     type Collection = CollectionModule;
+    type OnTransfer = ();
+    type OnMint = ();
+    type OnBurn = ();
+    type MaxBatchTransfers = MaxBatchTransfers;
+    type Currency = Balances;
+    type Signature = sp_runtime::testing::TestSignature;
+    type Signer = sp_runtime::testing::UintAuthorityId;
+    type RangeKeepers = (GraphModule, TemplateModule);
+    type ForceOrigin = frame_system::EnsureRoot<u64>;
+    type MaxTokenLocks = MaxTokenLocks;
 }
 
 impl pallet_template::Config for Test {
@@ -84,6 +105,17 @@ impl pallet_template::Config for Test {
     type Currency = Balances;
     type Collection = CollectionModule;
     type NFT = NFTModule;
+    type Graph = GraphModule;
+}
+
+impl pallet_graph::Config for Test {
+    type Event = Event;
+    type Collection = CollectionModule;
+    type NFT = NFTModule;
+    type StringLimit = StringLimit;
+    type MaxEquippables = MaxEquippables;
+    type MaxDepth = MaxDepth;
+    type MaxDescendants = MaxDescendants;
 }
 
 pub type BlockNumber = u64;
@@ -2,6 +2,7 @@ use crate::{mock::*, Error};
 use frame_support::traits::Currency;
 use frame_support::{assert_noop, assert_ok};
 use pallet_collection::CollectionInterface;
+use pallet_graph::GraphInterface;
 use sp_runtime::traits::SaturatedConversion;
 
 #[test]
@@ -157,7 +158,7 @@ fn buy_nft() {
 
         let order_id = TemplateModule::next_nft_order_id() - 1;
         // let order = TemplateModule::nft_order(order_id);
-        assert_ok!(TemplateModule::buy_nft(bob, order_id, 1));
+        assert_ok!(TemplateModule::buy_nft(bob, order_id, 1, None));
 
         let order = TemplateModule::nft_order(order_id);
         assert_eq!(&order.start_idx, &1_u128);
@@ -208,7 +209,7 @@ fn cancel_nft_order() {
 
         let order_id = TemplateModule::next_nft_order_id() - 1;
         // let order = TemplateModule::nft_order(order_id);
-        assert_ok!(TemplateModule::buy_nft(bob.clone(), order_id, 1));
+        assert_ok!(TemplateModule::buy_nft(bob.clone(), order_id, 1, None));
 
         let order = TemplateModule::nft_order(order_id);
         assert_eq!(&order.start_idx, &1_u128);
@@ -263,17 +264,96 @@ fn buy_nft_failed() {
         let order_id = TemplateModule::next_nft_order_id() - 1;
 
         assert_noop!(
-            TemplateModule::buy_nft(bob.clone(), order_id, 0),
+            TemplateModule::buy_nft(bob.clone(), order_id, 0, None),
             Error::<Test>::AmountLessThanOne
         );
         assert_noop!(
-            TemplateModule::buy_nft(bob.clone(), 1, 1),
+            TemplateModule::buy_nft(bob.clone(), 1, 1, None),
             Error::<Test>::OrderNotFound
         );
         assert_noop!(
-            TemplateModule::buy_nft(bob, order_id, 11),
+            TemplateModule::buy_nft(bob.clone(), order_id, 11, None),
             Error::<Test>::AmountTooLarge
         );
+
+        assert_ok!(NFTModule::freeze_account(Origin::root(), collection_id, alice_address));
+        assert_noop!(
+            TemplateModule::buy_nft(bob.clone(), order_id, 1, None),
+            Error::<Test>::Frozen
+        );
+
+        assert_ok!(NFTModule::thaw_account(Origin::root(), collection_id, alice_address));
+        assert_ok!(NFTModule::freeze_collection(Origin::root(), collection_id));
+        assert_noop!(
+            TemplateModule::buy_nft(bob, order_id, 1, None),
+            Error::<Test>::Frozen
+        );
+    });
+}
+
+#[test]
+fn buy_nft_with_subtree() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        let bob_address = 2;
+        let bob = Origin::signed(bob_address);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id =
+            <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        assert_ok!(NFTModule::mint_non_fungible(
+            alice.clone(),
+            alice_address,
+            collection_id,
+            vec![2, 3, 3],
+            3
+        ));
+
+        // 0 <- 1, anyone can link NFTs of the collection to 0
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), collection_id, 1, collection_id, 0));
+        assert_ok!(GraphModule::set_link_allowlist(alice.clone(), collection_id, 0, collection_id, true));
+
+        let price = 1_u128.saturated_into::<crate::BalanceOf<Test>>();
+        assert_ok!(TemplateModule::sell_nft(alice.clone(), collection_id, 0, 1, price));
+
+        let order_id = TemplateModule::next_nft_order_id() - 1;
+        let subtree_hash = <GraphModule as GraphInterface<_, _>>::subtree_hash(&collection_id, 0).unwrap();
+        assert!(subtree_hash.is_some());
+        assert_eq!(TemplateModule::nft_order(order_id).subtree_hash, subtree_hash);
+
+        let _ = Balances::deposit_creating(&bob_address, 2);
+
+        assert_noop!(
+            TemplateModule::buy_nft(bob.clone(), order_id, 1, None),
+            Error::<Test>::SubtreeChanged
+        );
+
+        // the subtree changed after listing, the order can't be bought with either hash
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), collection_id, 2, collection_id, 0));
+        let new_subtree_hash = <GraphModule as GraphInterface<_, _>>::subtree_hash(&collection_id, 0).unwrap();
+        assert_noop!(
+            TemplateModule::buy_nft(bob.clone(), order_id, 1, subtree_hash),
+            Error::<Test>::SubtreeChanged
+        );
+        assert_noop!(
+            TemplateModule::buy_nft(bob.clone(), order_id, 1, new_subtree_hash),
+            Error::<Test>::SubtreeChanged
+        );
+
+        // listed again with the new subtree
+        assert_ok!(TemplateModule::cancel_nft_order(alice.clone(), order_id));
+        assert_ok!(TemplateModule::sell_nft(alice, collection_id, 0, 1, price));
+        let order_id = TemplateModule::next_nft_order_id() - 1;
+        assert_ok!(TemplateModule::buy_nft(bob, order_id, 1, new_subtree_hash));
+
+        assert_eq!(NFTModule::tokens(collection_id, 0).owner, bob_address);
+        let mut descendants = <GraphModule as GraphInterface<_, _>>::descendants(&collection_id, 0);
+        descendants.sort();
+        assert_eq!(descendants, vec![(collection_id, 1), (collection_id, 2)]);
     });
 }
 
@@ -655,3 +735,79 @@ fn withdraw_pool() {
         );
     });
 }
+
+#[test]
+fn buy_nft_batch_with_subtree() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        let bob_address = 2;
+        let bob = Origin::signed(bob_address);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id =
+            <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        assert_ok!(NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 3));
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let child_collection_id =
+            <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        assert_ok!(NFTModule::mint_non_fungible(alice.clone(), alice_address, child_collection_id, vec![2, 3, 3], 1));
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), child_collection_id, 0, collection_id, 0));
+
+        // the hash of the first NFT is kept for a batch too
+        let price = 1_u128.saturated_into::<crate::BalanceOf<Test>>();
+        assert_ok!(TemplateModule::sell_nft(alice, collection_id, 0, 3, price));
+        let order_id = TemplateModule::next_nft_order_id() - 1;
+        let subtree_hash = <GraphModule as GraphInterface<_, _>>::subtree_hash(&collection_id, 0).unwrap();
+        assert!(subtree_hash.is_some());
+        assert_eq!(TemplateModule::nft_order(order_id).subtree_hash, subtree_hash);
+
+        let _ = Balances::deposit_creating(&bob_address, 10);
+        assert_noop!(
+            TemplateModule::buy_nft(bob.clone(), order_id, 2, None),
+            Error::<Test>::SubtreeChanged
+        );
+        assert_ok!(TemplateModule::buy_nft(bob.clone(), order_id, 1, subtree_hash));
+        assert_eq!(NFTModule::tokens(collection_id, 0).owner, bob_address);
+        assert_eq!(<GraphModule as GraphInterface<_, _>>::descendants(&collection_id, 0), vec![(child_collection_id, 0)]);
+
+        // the rest of the order starts at a NFT without subtree
+        let order = TemplateModule::nft_order(order_id);
+        assert_eq!(order.start_idx, 1);
+        assert_eq!(order.subtree_hash, None);
+        assert_ok!(TemplateModule::buy_nft(bob, order_id, 2, None));
+        assert_eq!(NFTModule::tokens(collection_id, 1).owner, bob_address);
+    });
+}
+
+#[test]
+fn migrate_to_v1_adds_subtree_hash() {
+    new_test_ext().execute_with(|| {
+        let collection_id = sp_core::H256::repeat_byte(1);
+        frame_support::storage::unhashed::put(
+            &crate::NonFungibleOrders::<Test>::hashed_key_for(0),
+            &crate::OldNonFungibleOrderInfo {
+                collection_id,
+                start_idx: 2,
+                seller: 1u64,
+                price: 10u64,
+                amount: 3,
+            },
+        );
+        assert_eq!(TemplateModule::storage_version(), 0);
+
+        TemplateModule::migrate_to_v1();
+
+        let order = TemplateModule::nft_order(0);
+        assert_eq!(order.collection_id, collection_id);
+        assert_eq!(order.start_idx, 2);
+        assert_eq!(order.seller, 1);
+        assert_eq!(order.price, 10);
+        assert_eq!(order.amount, 3);
+        assert_eq!(order.subtree_hash, None);
+        assert_eq!(TemplateModule::storage_version(), 1);
+    });
+}
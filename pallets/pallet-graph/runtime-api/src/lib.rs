@@ -0,0 +1,16 @@
+//! Runtime API definition for the graph pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use pallet_graph::Subtree;
+
+sp_api::decl_runtime_apis! {
+    pub trait GraphApi<Hash> where
+        Hash: Codec,
+    {
+        /// Get the NFTs and FTs linked to a NFT, directly or through other children, `None` if more
+        /// than `MaxDescendants` of either are linked.
+        fn subtree(collection_id: Hash, token_id: u128) -> Option<Subtree<Hash>>;
+    }
+}
@@ -9,6 +9,10 @@
 //! * **Child Token:** Link to the parent's token.
 //! * **Ancestor NFT:** NFT located before parent NFT or parent NFT.
 //! * **Root NFT:** Graph token's starting NFT.
+//! * **Base:** A parent collection that declares slots, only NFTs of collections equippable in one of
+//!   its slots can be linked to its NFTs.
+//! * **Slot:** A named place on the NFTs of a base, accepting children of some collections up to a
+//!   maximum count per parent NFT.
 //!
 //! ## Interface
 //!
@@ -18,24 +22,58 @@
 //! * `link_fungible` - Link some FTs to NFT.
 //! * `recover_non_fungible` - Transfer a child NFT to root_owner.
 //! * `recover_fungible` - Transfer some child FTs to root_owner.
+//! * `unlink_all` - Transfer the NFTs and FTs linked below a NFT to root_owner, in batches.
+//! * `burn_tree` - Burn a NFT with the NFTs and FTs linked below it, in batches.
+//! * `accept_link` - Accept a pending link of a NFT to a NFT of the root owner.
+//! * `reject_link` - Reject or withdraw a pending link, the child NFT returns to the sender.
+//! * `accept_fungible_link` - Accept FTs pending to be linked to a NFT of the root owner.
+//! * `reject_fungible_link` - Reject or withdraw FTs pending to be linked, they return to the sender.
+//! * `set_link_allowlist` - Let a child collection link to a NFT without approval.
+//! * `set_slot` - Declare a named slot on the NFTs of a collection.
+//! * `remove_slot` - Remove a slot of a collection.
+//! * `equip` - Put a linked child NFT into a slot of its parent.
+//! * `unequip` - Take a child NFT out of its slot, it stays linked.
 //! * `burn_fungible` - Destroy some FTs by owner
 //! * `burn_non_fungible` - Destroy one or a batch of NFTs NFTs by owner
 //!
+//! Tokens that are frozen in pallet-nft for the root owner can't be linked or recovered.
+//!
+//! Tokens linked to a NFT of another root owner are held by this pallet as pending until the root
+//! owner of the parent accepts them, unless their collection is in the allowlist of the parent.
+//!
+//! A NFT with linked NFTs or FTs can't be burned in pallet-nft, see `OnTokenBurn`, use `burn_tree`
+//! or `unlink_all` first.
+//!
+//! A NFT can be linked at most `MaxDepth` NFTs below its root NFT, and a NFT with more than
+//! `MaxDescendants` NFTs below it can't be linked or moved at once. Linked NFTs keep a pointer to
+//! their root NFT, so the root owner is found without walking up the graph.
+//!
+//! Other pallets read the graph through `GraphInterface`. A root NFT carries its whole subtree
+//! wherever it is transferred, because the root owner is always the owner of the root NFT.
+//!
 //! [`Call`]: ./enum.Call.html
 //! [`Config`]: ./trait.Config.html
 
 #![cfg_attr(not(feature = "std"), no_std)]
 #![recursion_limit = "128"]
+use codec::{Decode, Encode};
 use frame_support::{
     decl_error, decl_event, decl_module, decl_storage,
     dispatch::{DispatchError, DispatchResult},
     ensure,
+    traits::Get,
+    transactional,
+    weights::Weight,
 };
 use frame_system::ensure_signed;
-use sp_runtime::{traits::AccountIdConversion, ModuleId};
+use sp_runtime::{
+    traits::{AccountIdConversion, Hash as HashT},
+    ModuleId,
+};
+use sp_std::vec::Vec;
 
 use pallet_collection::CollectionInterface;
-use pallet_nft::NFTInterface;
+use pallet_nft::{KeepsTokenRanges, NFTInterface, OnTokenBurn};
 
 #[cfg(test)]
 mod mock;
@@ -45,11 +83,49 @@ mod tests;
 
 const PALLET_ID: ModuleId = ModuleId(*b"GraphNFT");
 
+/// A NFT waiting for the root owner of `parent` to accept it.
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct PendingLink<AccountId, Hash> {
+    pub sender: AccountId,
+    /// Parent(collection_id, token_id)
+    pub parent: (Hash, u128),
+}
+
+/// A slot on the NFTs of a base collection.
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Slot<Hash> {
+    /// The collections whose NFTs can be equipped.
+    pub equippable: Vec<Hash>,
+    /// How many children can be equipped in the slot of one parent NFT.
+    pub max: u32,
+}
+
+/// Everything linked to a NFT, directly or through other children.
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct Subtree<Hash> {
+    /// Linked NFTs as (child, parent).
+    pub non_fungible: Vec<((Hash, u128), (Hash, u128))>,
+    /// Linked FTs as (parent, fungible_collection_id, amount).
+    pub fungible: Vec<((Hash, u128), Hash, u128)>,
+}
+
 // pub trait Config: frame_system::Config + pallet_collection::Config + pallet_nft::Config {
 pub trait Config: frame_system::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
     type Collection: CollectionInterface<Self::Hash, Self::AccountId>;
     type NFT: NFTInterface<Self::Hash, Self::AccountId>;
+    /// The maximum length of a slot name.
+    type StringLimit: Get<u32>;
+    /// The maximum number of collections equippable in a slot.
+    type MaxEquippables: Get<u32>;
+    /// The maximum number of NFTs between a linked NFT and its root NFT, itself included.
+    type MaxDepth: Get<u32>;
+    /// The maximum number of NFTs linked below a NFT that is linked or moved, and of NFTs or
+    /// FT balances returned for the subtree of a NFT.
+    type MaxDescendants: Get<u32>;
 }
 
 decl_storage! {
@@ -60,6 +136,23 @@ decl_storage! {
         pub ParentToChild get(fn parent_to_child): double_map hasher(blake2_128_concat) (T::Hash, u128), hasher(blake2_128_concat) (T::Hash, u128) => ();
         // How many tokens are linked to the parent. (parent_token, child_collection_id) => balance
         pub ParentBalance get(fn parent_balance): double_map hasher(blake2_128_concat) (T::Hash, u128), hasher(blake2_128_concat) T::Hash => u128;
+        /// NFTs waiting to be linked. Child(collection_id, token_id) => request
+        pub PendingLinks get(fn pending_link): map hasher(blake2_128_concat) (T::Hash, u128) => Option<PendingLink<T::AccountId, T::Hash>>;
+        /// FTs waiting to be linked. Parent(collection_id, token_id), (sender, fungible_collection_id) => amount
+        pub PendingFungibleLinks get(fn pending_fungible_link): double_map hasher(blake2_128_concat) (T::Hash, u128), hasher(blake2_128_concat) (T::AccountId, T::Hash) => u128;
+        /// Child collections linked to a NFT without approval. Parent(collection_id, token_id), child_collection_id => ()
+        pub LinkAllowlist get(fn link_allowlist): double_map hasher(blake2_128_concat) (T::Hash, u128), hasher(blake2_128_concat) T::Hash => ();
+        /// The slots declared by a base collection. collection_id, slot => slot
+        pub Slots get(fn slot): double_map hasher(blake2_128_concat) T::Hash, hasher(blake2_128_concat) Vec<u8> => Option<Slot<T::Hash>>;
+        /// The slot a child NFT is equipped in. Child(collection_id, token_id) => slot
+        pub EquippedSlot get(fn equipped_slot): map hasher(blake2_128_concat) (T::Hash, u128) => Option<Vec<u8>>;
+        /// How many children are equipped in a slot of a parent NFT. (Parent(collection_id, token_id), slot) => count
+        pub SlotUsage get(fn slot_usage): double_map hasher(blake2_128_concat) (T::Hash, u128), hasher(blake2_128_concat) Vec<u8> => u32;
+        /// The root NFT of a linked NFT, or the pending NFT it is linked below. Child(collection_id, token_id) => Root(collection_id, token_id)
+        pub RootOf get(fn root_of): map hasher(blake2_128_concat) (T::Hash, u128) => Option<(T::Hash, u128)>;
+
+        /// The version of storage, used by migrations.
+        pub StorageVersion get(fn storage_version): u32;
     }
 }
 
@@ -68,6 +161,7 @@ decl_event!(
     pub enum Event<T>
     where
         AccountId = <T as frame_system::Config>::AccountId,
+        Hash = <T as frame_system::Config>::Hash,
     {
         /// NFT was linked to another NFT. \[who\]
         NonFungibleTokenLinked(AccountId),
@@ -79,6 +173,32 @@ decl_event!(
         NonFungibleTokenRecovered(AccountId),
         // Child FTs were transferred to root_owner. \[who\]
         FungibleTokenRecovered(AccountId),
+        /// Tokens linked below a NFT were transferred to root_owner. \[who, collection_id, token_id, finished\]
+        SubtreeUnlinked(AccountId, Hash, u128, bool),
+        /// Tokens linked below a NFT were burned, and the NFT too once finished. \[who, collection_id, token_id, finished\]
+        TreeBurned(AccountId, Hash, u128, bool),
+        /// A NFT is waiting to be linked to a NFT of another root owner. \[sender, collection_id, token_id, parent_collection_id, parent_token_id\]
+        LinkRequested(AccountId, Hash, u128, Hash, u128),
+        /// A pending NFT was linked. \[who, collection_id, token_id\]
+        LinkAccepted(AccountId, Hash, u128),
+        /// A pending NFT was returned to the sender. \[who, collection_id, token_id\]
+        LinkRejected(AccountId, Hash, u128),
+        /// FTs are waiting to be linked to a NFT of another root owner. \[sender, fungible_collection_id, parent_collection_id, parent_token_id, amount\]
+        FungibleLinkRequested(AccountId, Hash, Hash, u128, u128),
+        /// Pending FTs were linked. \[who, fungible_collection_id, parent_collection_id, parent_token_id, amount\]
+        FungibleLinkAccepted(AccountId, Hash, Hash, u128, u128),
+        /// Pending FTs were returned to the sender. \[who, fungible_collection_id, parent_collection_id, parent_token_id, amount\]
+        FungibleLinkRejected(AccountId, Hash, Hash, u128, u128),
+        /// A child collection was added to or removed from the allowlist of a NFT. \[parent_collection_id, parent_token_id, child_collection_id, allowed\]
+        LinkAllowlistSet(Hash, u128, Hash, bool),
+        /// A slot was declared or changed. \[collection_id, slot\]
+        SlotSet(Hash, Vec<u8>),
+        /// A slot was removed. \[collection_id, slot\]
+        SlotRemoved(Hash, Vec<u8>),
+        /// A child NFT was equipped in a slot of its parent. \[who, collection_id, token_id, slot\]
+        Equipped(AccountId, Hash, u128, Vec<u8>),
+        /// A child NFT was taken out of its slot. \[who, collection_id, token_id\]
+        Unequipped(AccountId, Hash, u128),
     }
 );
 
@@ -105,6 +225,30 @@ decl_error! {
         ChildTokenNotFound,
         /// Can't recover a parent NFT.
         CanNotRecoverParentToken,
+        /// The collection or the tokens of the root owner are frozen.
+        Frozen,
+        /// There is no pending link.
+        LinkRequestNotFound,
+        /// The slot name is empty or too long.
+        BadSlotName,
+        /// Too many equippable collections.
+        TooManyEquippables,
+        /// Slot does not exist.
+        SlotNotFound,
+        /// The NFT's collection can't be equipped in the slot or linked to the base.
+        NotEquippable,
+        /// The slot of the parent NFT is full.
+        SlotFull,
+        /// The child NFT is already equipped.
+        AlreadyEquipped,
+        /// The child NFT is not equipped.
+        NotEquipped,
+        /// Can't burn a NFT that has linked NFTs or FTs.
+        ParentTokenHasChildren,
+        /// The NFT would be linked more than `MaxDepth` NFTs below its root NFT.
+        TooDeep,
+        /// More than `MaxDescendants` NFTs, or FT balances, are linked below the NFT.
+        TooManyDescendants,
     }
 }
 
@@ -114,10 +258,19 @@ decl_module! {
 
         fn deposit_event() = default;
 
+        fn on_runtime_upgrade() -> Weight {
+            if Self::storage_version() == 0 {
+                Self::migrate_to_v1()
+            } else {
+                0
+            }
+        }
+
         /// Link a NFT to another NFT.
         ///
         /// Note: Ancestor NFT cannot be linked to descendant NFT.
         /// After linking to an NFT, the owner of the child NFT will become the root_owner
+        /// If the parent NFT has another root owner, the child NFT is pending until it is accepted.
         ///
         /// The dispatch origin of this call must be _Signed_.
         ///
@@ -126,7 +279,8 @@ decl_module! {
         /// - `child_token_id`: The index of the child NFT.
         /// - `parent_collection_id`: The collection in which parebt NFT is located.
         /// - `parent_token_id`: The index of the parent NFT.
-        #[weight = 10_000]
+        #[weight = 10_000 + 10_000 * ((T::MaxDepth::get() + T::MaxDescendants::get()) as Weight)]
+        #[transactional]
         pub fn link_non_fungible(origin, child_collection_id: T::Hash, child_token_id: u128, parent_collection_id: T::Hash, parent_token_id: u128) -> DispatchResult {
             ensure!(
                 T::Collection::collection_exist(parent_collection_id),
@@ -138,35 +292,44 @@ decl_module! {
             );
 
             let who = ensure_signed(origin)?;
+            ensure!(Self::accepts_child(&parent_collection_id, &child_collection_id), Error::<T>::NotEquippable);
             let have_parent = ChildToParent::<T>::contains_key((child_collection_id, child_token_id));
 
             if have_parent {
                 // if token in ChildToParent, it's owner is graph pallet.
                 let root_token_owner = Self::find_root_owner(child_collection_id, child_token_id)?;
                 ensure!(root_token_owner == who, Error::<T>::PermissionDenied);
+                ensure!(!T::NFT::is_frozen(&child_collection_id, &who), Error::<T>::Frozen);
             } else {
                 // token's owner should be user
                 T::NFT::_transfer_non_fungible(who.clone(), Self::account_id(), child_collection_id, child_token_id, 1)?;
             }
 
-            // if parent token's owner is user, it can be a root token, so don't check
-            let parent_token = T::NFT::get_nft_token(parent_collection_id, parent_token_id);
-
-            if parent_token.owner == Self::account_id() {
-                let child_is_parent_ancestor = Self::is_ancestor((child_collection_id, child_token_id), (parent_collection_id, parent_token_id))?;
-                ensure!(
-                    !child_is_parent_ancestor,
-                    Error::<T>::CanNotLinkAncestorToDescendant
-                );
-            }
+            let (root, descendants) = Self::prepare_link((child_collection_id, child_token_id), (parent_collection_id, parent_token_id))?;
 
             if have_parent {
+                Self::unequip_token((child_collection_id, child_token_id));
                 let (old_parent_collection_id, old_parent_token_id) = Self::child_to_parent((child_collection_id, child_token_id));
                 ParentToChild::<T>::remove((old_parent_collection_id, old_parent_token_id), (child_collection_id, child_token_id));
             }
 
+            if Self::needs_consent(&who, (parent_collection_id, parent_token_id), &child_collection_id)? {
+                // the child is held by this pallet without parent until accepted or rejected.
+                ChildToParent::<T>::remove((child_collection_id, child_token_id));
+                Self::set_root((child_collection_id, child_token_id), None, &descendants);
+                PendingLinks::<T>::insert((child_collection_id, child_token_id), PendingLink {
+                    sender: who.clone(),
+                    parent: (parent_collection_id, parent_token_id),
+                });
+
+                Self::deposit_event(RawEvent::LinkRequested(who, child_collection_id, child_token_id, parent_collection_id, parent_token_id));
+
+                return Ok(());
+            }
+
             ChildToParent::<T>::insert((child_collection_id, child_token_id), (parent_collection_id, parent_token_id));
             ParentToChild::<T>::insert((parent_collection_id, parent_token_id), (child_collection_id, child_token_id), ());
+            Self::set_root((child_collection_id, child_token_id), Some(root), &descendants);
 
             Self::deposit_event(RawEvent::NonFungibleTokenLinked(who));
 
@@ -176,6 +339,7 @@ decl_module! {
         /// Link a FTs to NFT.
         ///
         /// If there is no child_collection_id and child_token_id, then FTs will be transferred from the user.
+        /// If the parent NFT has another root owner, the FTs are pending until they are accepted.
         ///
         /// The dispatch origin of this call must be _Signed_.
         ///
@@ -187,6 +351,7 @@ decl_module! {
         /// - `parent_token_id`: The index of the parent NFT.
         /// - `amount`: Amount of FTs to link to parent NFT.
         #[weight = 10_000]
+        #[transactional]
         pub fn link_fungible(origin, child_collection_id: Option<T::Hash>, child_token_id: Option<u128>, fungible_collection_id: T::Hash, parent_collection_id: T::Hash, parent_token_id: u128, amount: u128) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
@@ -203,9 +368,14 @@ decl_module! {
             // let transfer_from_user = child_token.is_none();
             let parent_balance = Self::parent_balance((parent_collection_id, parent_token_id), fungible_collection_id).checked_add(amount).ok_or(Error::<T>::NumOverflow)?;
 
+            let needs_consent = Self::needs_consent(&who, (parent_collection_id, parent_token_id), &fungible_collection_id)?;
+
             if transfer_from_user {
                 // <pallet_nft::Module<T>>::transfer_fungible(origin, Self::account_id(), fungible_collection_id, amount)?;
                 T::NFT::_transfer_fungible(who.clone(), Self::account_id(), fungible_collection_id, amount)?;
+                if needs_consent {
+                    return Self::request_fungible_link(who, fungible_collection_id, (parent_collection_id, parent_token_id), amount);
+                }
                 ParentBalance::<T>::insert((parent_collection_id, parent_token_id), fungible_collection_id, parent_balance);
 
                 Self::deposit_event(RawEvent::FungibleTokenLinkedByUser(who));
@@ -221,14 +391,19 @@ decl_module! {
 
                 let root_token_owner = Self::find_root_owner(child_collection_id, child_token_id)?;
                 ensure!(root_token_owner == who, Error::<T>::PermissionDenied);
+                ensure!(!T::NFT::is_frozen(&fungible_collection_id, &who), Error::<T>::Frozen);
 
                 ParentBalance::<T>::insert((child_collection_id, child_token_id), fungible_collection_id, child_balance);
-                ParentBalance::<T>::insert((parent_collection_id, parent_token_id), fungible_collection_id, parent_balance);
 
                 if child_balance == 0 {
                     ParentBalance::<T>::remove((child_collection_id, child_token_id), fungible_collection_id);
                 }
 
+                if needs_consent {
+                    return Self::request_fungible_link(who, fungible_collection_id, (parent_collection_id, parent_token_id), amount);
+                }
+                ParentBalance::<T>::insert((parent_collection_id, parent_token_id), fungible_collection_id, parent_balance);
+
                 Self::deposit_event(RawEvent::FungibleTokenLinkedByChild(who));
             }
 
@@ -263,11 +438,12 @@ decl_module! {
             let root_token_owner = Self::find_root_owner(collection_id, token_id)?;
 
             ensure!(root_token_owner == who, Error::<T>::PermissionDenied);
+            ensure!(!T::NFT::is_frozen(&collection_id, &who), Error::<T>::Frozen);
 
             // <pallet_nft::Module<T>>::transfer_non_fungible(frame_system::RawOrigin::Signed(Self::account_id()).into(), who.clone(), collection_id, token_id, 1)?;
             T::NFT::_transfer_non_fungible(Self::account_id(), who.clone(), collection_id, token_id, 1)?;
 
-            ChildToParent::<T>::remove((collection_id, token_id));
+            Self::detach((collection_id, token_id));
 
             Self::deposit_event(RawEvent::NonFungibleTokenRecovered(who));
 
@@ -292,6 +468,7 @@ decl_module! {
 
             let root_token_owner = Self::find_root_owner(child_collection_id, child_token_id)?;
             ensure!(root_token_owner == who, Error::<T>::PermissionDenied);
+            ensure!(!T::NFT::is_frozen(&fungible_collection_id, &who), Error::<T>::Frozen);
 
             let child_balance = child_balance.checked_sub(amount).ok_or(Error::<T>::NumOverflow)?;
 
@@ -308,6 +485,398 @@ decl_module! {
             Ok(())
         }
 
+        /// Transfer all NFTs and FTs linked below a NFT to root_owner, the NFT itself stays where it is.
+        ///
+        /// Tokens are recovered leaves first, if more than `max_steps` tokens are linked, call it again to continue.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the root owner of the NFT.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The collection id of the NFT.
+        /// - `token_id`: The index of the NFT.
+        /// - `max_steps`: The maximum number of NFTs and FT balances to recover in this call.
+        #[weight = 10_000 + 10_000 * (max_steps as Weight) * (T::MaxDepth::get() as Weight)]
+        #[transactional]
+        pub fn unlink_all(origin, collection_id: T::Hash, token_id: u128, max_steps: u32) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(T::NFT::token_exist(collection_id, token_id), Error::<T>::TokenNotFound);
+            let root_token_owner = Self::find_root_owner(collection_id, token_id)?;
+            ensure!(root_token_owner == who, Error::<T>::PermissionDenied);
+
+            let finished = Self::clear_subtree(&who, (collection_id, token_id), max_steps, false)?;
+
+            Self::deposit_event(RawEvent::SubtreeUnlinked(who, collection_id, token_id, finished));
+
+            Ok(())
+        }
+
+        /// Burn a NFT with all NFTs and FTs linked below it.
+        ///
+        /// Tokens are burned leaves first and the NFT is burned last, if more than `max_steps` tokens are linked,
+        /// call it again to continue.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the root owner of the NFT.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The collection id of the NFT.
+        /// - `token_id`: The index of the NFT.
+        /// - `max_steps`: The maximum number of NFTs and FT balances to burn in this call.
+        #[weight = 10_000 + 10_000 * (max_steps as Weight) * (T::MaxDepth::get() as Weight)]
+        #[transactional]
+        pub fn burn_tree(origin, collection_id: T::Hash, token_id: u128, max_steps: u32) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(T::NFT::token_exist(collection_id, token_id), Error::<T>::TokenNotFound);
+            let root_token_owner = Self::find_root_owner(collection_id, token_id)?;
+            ensure!(root_token_owner == who, Error::<T>::PermissionDenied);
+
+            let finished = Self::clear_subtree(&who, (collection_id, token_id), max_steps, true)?;
+            if finished {
+                LinkAllowlist::<T>::remove_prefix((collection_id, token_id));
+                if ChildToParent::<T>::contains_key((collection_id, token_id)) {
+                    Self::detach((collection_id, token_id));
+                    T::NFT::_burn_non_fungible(Self::account_id(), collection_id, token_id, 1)?;
+                } else {
+                    T::NFT::_burn_non_fungible(who.clone(), collection_id, token_id, 1)?;
+                }
+            }
+
+            Self::deposit_event(RawEvent::TreeBurned(who, collection_id, token_id, finished));
+
+            Ok(())
+        }
+
+        /// Accept a NFT pending to be linked to a NFT of the sender.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the root owner of the parent NFT.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The collection in which child NFT is located.
+        /// - `token_id`: The index of the child NFT.
+        #[weight = 10_000 + 10_000 * ((T::MaxDepth::get() + T::MaxDescendants::get()) as Weight)]
+        pub fn accept_link(origin, collection_id: T::Hash, token_id: u128) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let pending = Self::pending_link((collection_id, token_id)).ok_or(Error::<T>::LinkRequestNotFound)?;
+            let (parent_collection_id, parent_token_id) = pending.parent;
+            ensure!(
+                T::NFT::token_exist(parent_collection_id, parent_token_id),
+                Error::<T>::TokenNotFound
+            );
+            let root_token_owner = Self::find_root_owner(parent_collection_id, parent_token_id)?;
+            ensure!(root_token_owner == who, Error::<T>::PermissionDenied);
+            ensure!(Self::accepts_child(&parent_collection_id, &collection_id), Error::<T>::NotEquippable);
+
+            // the parent may have been linked below the child since the request.
+            let (root, descendants) = Self::prepare_link((collection_id, token_id), (parent_collection_id, parent_token_id))?;
+
+            PendingLinks::<T>::remove((collection_id, token_id));
+            ChildToParent::<T>::insert((collection_id, token_id), (parent_collection_id, parent_token_id));
+            ParentToChild::<T>::insert((parent_collection_id, parent_token_id), (collection_id, token_id), ());
+            Self::set_root((collection_id, token_id), Some(root), &descendants);
+
+            Self::deposit_event(RawEvent::LinkAccepted(who, collection_id, token_id));
+
+            Ok(())
+        }
+
+        /// Reject a pending NFT, or withdraw it as its sender. The NFT is transferred back to the sender.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the root owner of
+        /// the parent NFT or the sender of the link.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The collection in which child NFT is located.
+        /// - `token_id`: The index of the child NFT.
+        #[weight = 10_000]
+        pub fn reject_link(origin, collection_id: T::Hash, token_id: u128) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let pending = Self::pending_link((collection_id, token_id)).ok_or(Error::<T>::LinkRequestNotFound)?;
+            ensure!(
+                pending.sender == who || Self::is_root_owner(&who, pending.parent),
+                Error::<T>::PermissionDenied
+            );
+
+            T::NFT::_transfer_non_fungible(Self::account_id(), pending.sender, collection_id, token_id, 1)?;
+            PendingLinks::<T>::remove((collection_id, token_id));
+
+            Self::deposit_event(RawEvent::LinkRejected(who, collection_id, token_id));
+
+            Ok(())
+        }
+
+        /// Accept FTs pending to be linked to a NFT of the sender.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the root owner of the parent NFT.
+        ///
+        /// Parameters:
+        /// - `parent_collection_id`: The collection in which parent NFT is located.
+        /// - `parent_token_id`: The index of the parent NFT.
+        /// - `sender`: The account that linked the FTs.
+        /// - `fungible_collection_id`: The collection in which FTs are located.
+        #[weight = 10_000]
+        pub fn accept_fungible_link(origin, parent_collection_id: T::Hash, parent_token_id: u128, sender: T::AccountId, fungible_collection_id: T::Hash) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let parent = (parent_collection_id, parent_token_id);
+            let amount = Self::pending_fungible_link(parent, (&sender, fungible_collection_id));
+            ensure!(amount > 0, Error::<T>::LinkRequestNotFound);
+            let root_token_owner = Self::find_root_owner(parent_collection_id, parent_token_id)?;
+            ensure!(root_token_owner == who, Error::<T>::PermissionDenied);
+
+            let parent_balance = Self::parent_balance(parent, fungible_collection_id).checked_add(amount).ok_or(Error::<T>::NumOverflow)?;
+            PendingFungibleLinks::<T>::remove(parent, (&sender, fungible_collection_id));
+            ParentBalance::<T>::insert(parent, fungible_collection_id, parent_balance);
+
+            Self::deposit_event(RawEvent::FungibleLinkAccepted(who, fungible_collection_id, parent_collection_id, parent_token_id, amount));
+
+            Ok(())
+        }
+
+        /// Reject pending FTs, or withdraw them as their sender. The FTs are transferred back to the sender.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the root owner of
+        /// the parent NFT or the sender of the link.
+        ///
+        /// Parameters:
+        /// - `parent_collection_id`: The collection in which parent NFT is located.
+        /// - `parent_token_id`: The index of the parent NFT.
+        /// - `sender`: The account that linked the FTs.
+        /// - `fungible_collection_id`: The collection in which FTs are located.
+        #[weight = 10_000]
+        pub fn reject_fungible_link(origin, parent_collection_id: T::Hash, parent_token_id: u128, sender: T::AccountId, fungible_collection_id: T::Hash) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let parent = (parent_collection_id, parent_token_id);
+            let amount = Self::pending_fungible_link(parent, (&sender, fungible_collection_id));
+            ensure!(amount > 0, Error::<T>::LinkRequestNotFound);
+            ensure!(sender == who || Self::is_root_owner(&who, parent), Error::<T>::PermissionDenied);
+
+            T::NFT::_transfer_fungible(Self::account_id(), sender.clone(), fungible_collection_id, amount)?;
+            PendingFungibleLinks::<T>::remove(parent, (&sender, fungible_collection_id));
+
+            Self::deposit_event(RawEvent::FungibleLinkRejected(who, fungible_collection_id, parent_collection_id, parent_token_id, amount));
+
+            Ok(())
+        }
+
+        /// Add a child collection to the allowlist of a NFT, or remove it. Tokens of the collection
+        /// are linked to the NFT without approval.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the root owner of the parent NFT.
+        ///
+        /// Parameters:
+        /// - `parent_collection_id`: The collection in which parent NFT is located.
+        /// - `parent_token_id`: The index of the parent NFT.
+        /// - `child_collection_id`: The collection of the NFTs or FTs to allow.
+        /// - `allowed`: Add to or remove from the allowlist.
+        #[weight = 10_000]
+        pub fn set_link_allowlist(origin, parent_collection_id: T::Hash, parent_token_id: u128, child_collection_id: T::Hash, allowed: bool) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                T::NFT::token_exist(parent_collection_id, parent_token_id),
+                Error::<T>::TokenNotFound
+            );
+            let root_token_owner = Self::find_root_owner(parent_collection_id, parent_token_id)?;
+            ensure!(root_token_owner == who, Error::<T>::PermissionDenied);
+
+            if allowed {
+                LinkAllowlist::<T>::insert((parent_collection_id, parent_token_id), child_collection_id, ());
+            } else {
+                LinkAllowlist::<T>::remove((parent_collection_id, parent_token_id), child_collection_id);
+            }
+
+            Self::deposit_event(RawEvent::LinkAllowlistSet(parent_collection_id, parent_token_id, child_collection_id, allowed));
+
+            Ok(())
+        }
+
+        /// Declare or change a slot on the NFTs of a collection, which makes the collection a base.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the owner of the collection.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The base collection.
+        /// - `slot`: The name of the slot.
+        /// - `equippable`: The collections whose NFTs can be equipped in the slot.
+        /// - `max`: How many children can be equipped in the slot of one NFT.
+        #[weight = 10_000]
+        pub fn set_slot(origin, collection_id: T::Hash, slot: Vec<u8>, equippable: Vec<T::Hash>, max: u32) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                T::Collection::collection_exist(collection_id),
+                Error::<T>::ParentCollectionNotFound
+            );
+            ensure!(T::Collection::get_collection(collection_id).owner == who, Error::<T>::PermissionDenied);
+            ensure!(!slot.is_empty() && slot.len() as u32 <= T::StringLimit::get(), Error::<T>::BadSlotName);
+            ensure!(equippable.len() as u32 <= T::MaxEquippables::get(), Error::<T>::TooManyEquippables);
+
+            Slots::<T>::insert(collection_id, &slot, Slot { equippable, max });
+
+            Self::deposit_event(RawEvent::SlotSet(collection_id, slot));
+
+            Ok(())
+        }
+
+        /// Remove a slot of a collection. Children equipped in it stay equipped until unequipped.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the owner of the collection.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The base collection.
+        /// - `slot`: The name of the slot.
+        #[weight = 10_000]
+        pub fn remove_slot(origin, collection_id: T::Hash, slot: Vec<u8>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(Slots::<T>::contains_key(collection_id, &slot), Error::<T>::SlotNotFound);
+            ensure!(T::Collection::get_collection(collection_id).owner == who, Error::<T>::PermissionDenied);
+
+            Slots::<T>::remove(collection_id, &slot);
+
+            Self::deposit_event(RawEvent::SlotRemoved(collection_id, slot));
+
+            Ok(())
+        }
+
+        /// Equip a linked child NFT in a slot of its parent NFT.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the root owner.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The collection in which child NFT is located.
+        /// - `token_id`: The index of the child NFT.
+        /// - `slot`: The slot of the parent NFT.
+        #[weight = 10_000]
+        pub fn equip(origin, collection_id: T::Hash, token_id: u128, slot: Vec<u8>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                ChildToParent::<T>::contains_key((collection_id, token_id)),
+                Error::<T>::ChildTokenNotFound
+            );
+            ensure!(!EquippedSlot::<T>::contains_key((collection_id, token_id)), Error::<T>::AlreadyEquipped);
+
+            let root_token_owner = Self::find_root_owner(collection_id, token_id)?;
+            ensure!(root_token_owner == who, Error::<T>::PermissionDenied);
+
+            let parent = Self::child_to_parent((collection_id, token_id));
+            let slot_info = Self::slot(parent.0, &slot).ok_or(Error::<T>::SlotNotFound)?;
+            ensure!(slot_info.equippable.contains(&collection_id), Error::<T>::NotEquippable);
+
+            let usage = Self::slot_usage(parent, &slot);
+            ensure!(usage < slot_info.max, Error::<T>::SlotFull);
+
+            SlotUsage::<T>::insert(parent, &slot, usage + 1);
+            EquippedSlot::<T>::insert((collection_id, token_id), &slot);
+
+            Self::deposit_event(RawEvent::Equipped(who, collection_id, token_id, slot));
+
+            Ok(())
+        }
+
+        /// Take a child NFT out of its slot, it stays linked to its parent.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the root owner.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The collection in which child NFT is located.
+        /// - `token_id`: The index of the child NFT.
+        #[weight = 10_000]
+        pub fn unequip(origin, collection_id: T::Hash, token_id: u128) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(EquippedSlot::<T>::contains_key((collection_id, token_id)), Error::<T>::NotEquipped);
+
+            let root_token_owner = Self::find_root_owner(collection_id, token_id)?;
+            ensure!(root_token_owner == who, Error::<T>::PermissionDenied);
+
+            Self::unequip_token((collection_id, token_id));
+
+            Self::deposit_event(RawEvent::Unequipped(who, collection_id, token_id));
+
+            Ok(())
+        }
+
+    }
+}
+
+/// Read the graph from other pallets.
+pub trait GraphInterface<Hash, AccountId> {
+    /// Whether the NFT is linked to a parent NFT.
+    fn is_linked(collection_id: &Hash, token_id: u128) -> bool;
+    /// All NFTs linked to the NFT directly or through other children.
+    fn descendants(collection_id: &Hash, token_id: u128) -> Vec<(Hash, u128)>;
+    /// A hash of the subtree of the NFT, `None` if nothing is linked to it. Fails if the subtree
+    /// is too large to be hashed whole.
+    fn subtree_hash(collection_id: &Hash, token_id: u128) -> Result<Option<Hash>, DispatchError>;
+}
+
+impl<Hash, AccountId> GraphInterface<Hash, AccountId> for () {
+    fn is_linked(_collection_id: &Hash, _token_id: u128) -> bool {
+        false
+    }
+
+    fn descendants(_collection_id: &Hash, _token_id: u128) -> Vec<(Hash, u128)> {
+        Vec::new()
+    }
+
+    fn subtree_hash(_collection_id: &Hash, _token_id: u128) -> Result<Option<Hash>, DispatchError> {
+        Ok(None)
+    }
+}
+
+impl<T: Config> GraphInterface<T::Hash, T::AccountId> for Module<T> {
+    fn is_linked(collection_id: &T::Hash, token_id: u128) -> bool {
+        ChildToParent::<T>::contains_key((*collection_id, token_id))
+    }
+
+    fn descendants(collection_id: &T::Hash, token_id: u128) -> Vec<(T::Hash, u128)> {
+        let mut descendants = Vec::new();
+        let mut pending = sp_std::vec![(*collection_id, token_id)];
+        while let Some(parent) = pending.pop() {
+            for (child, _) in ParentToChild::<T>::iter_prefix(parent) {
+                descendants.push(child);
+                pending.push(child);
+            }
+        }
+        descendants
+    }
+
+    fn subtree_hash(collection_id: &T::Hash, token_id: u128) -> Result<Option<T::Hash>, DispatchError> {
+        let subtree = Self::subtree(*collection_id, token_id)?;
+        if subtree.non_fungible.is_empty() && subtree.fungible.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(T::Hashing::hash_of(&subtree)))
+        }
+    }
+}
+
+impl<T: Config> OnTokenBurn<T::Hash, T::AccountId> for Module<T> {
+    /// Linked NFTs and FTs would be left without root owner, parent NFTs can only be burned with `burn_tree`.
+    /// A parent NFT is always the start_idx of its own range, see `keeps_range`.
+    fn on_burn(collection_id: &T::Hash, range: Option<(u128, u128)>, _from: &T::AccountId, _amount: u128) -> DispatchResult {
+        if let Some((start_idx, _)) = range {
+            ensure!(!Self::has_children((*collection_id, start_idx)), Error::<T>::ParentTokenHasChildren);
+        }
+        Ok(())
+    }
+}
+
+impl<T: Config> KeepsTokenRanges<T::Hash, T::AccountId> for Module<T> {
+    /// Linked NFTs and parent NFTs are referenced by their start_idx, so are parents with
+    /// pending FT links or an allowlist.
+    fn keeps_range(collection_id: &T::Hash, start_idx: u128, owner: &T::AccountId) -> bool {
+        let token = (*collection_id, start_idx);
+        *owner == Self::account_id()
+            || Self::has_children(token)
+            || PendingFungibleLinks::<T>::iter_prefix(token).next().is_some()
+            || LinkAllowlist::<T>::iter_prefix(token).next().is_some()
     }
 }
 
@@ -316,63 +885,235 @@ impl<T: Config> Module<T> {
     pub fn account_id() -> T::AccountId {
         PALLET_ID.into_account()
     }
+    /// The children equipped in the slots of a parent NFT as (slot, child).
+    pub fn equipped(parent_collection_id: T::Hash, parent_token_id: u128) -> Vec<(Vec<u8>, (T::Hash, u128))> {
+        ParentToChild::<T>::iter_prefix((parent_collection_id, parent_token_id))
+            .filter_map(|(child, _)| Self::equipped_slot(child).map(|slot| (slot, child)))
+            .collect()
+    }
+
+    /// The NFTs and FTs linked to a NFT, directly or through other children. Fails rather than
+    /// leave out anything if more than `MaxDescendants` of either are linked.
+    pub fn subtree(collection_id: T::Hash, token_id: u128) -> Result<Subtree<T::Hash>, DispatchError> {
+        let max = T::MaxDescendants::get() as usize;
+        let mut subtree = Subtree::default();
+        let mut pending = sp_std::vec![(collection_id, token_id)];
+        while let Some(parent) = pending.pop() {
+            for (fungible_collection_id, amount) in ParentBalance::<T>::iter_prefix(parent) {
+                ensure!(subtree.fungible.len() < max, Error::<T>::TooManyDescendants);
+                subtree.fungible.push((parent, fungible_collection_id, amount));
+            }
+            for (child, _) in ParentToChild::<T>::iter_prefix(parent) {
+                ensure!(subtree.non_fungible.len() < max, Error::<T>::TooManyDescendants);
+                subtree.non_fungible.push((child, parent));
+                pending.push(child);
+            }
+        }
+        Ok(subtree)
+    }
+
+    /// Whether tokens of `child_collection_id` linked to `parent` by `who` wait for the root owner of the parent.
+    fn needs_consent(who: &T::AccountId, parent: (T::Hash, u128), child_collection_id: &T::Hash) -> Result<bool, DispatchError> {
+        let root_token_owner = Self::find_root_owner(parent.0, parent.1)?;
+        Ok(root_token_owner != *who && !LinkAllowlist::<T>::contains_key(parent, child_collection_id))
+    }
+
+    fn is_root_owner(who: &T::AccountId, token: (T::Hash, u128)) -> bool {
+        T::NFT::token_exist(token.0, token.1) && Self::find_root_owner(token.0, token.1).ok().as_ref() == Some(who)
+    }
+
+    fn request_fungible_link(who: T::AccountId, fungible_collection_id: T::Hash, parent: (T::Hash, u128), amount: u128) -> DispatchResult {
+        let pending = Self::pending_fungible_link(parent, (&who, fungible_collection_id)).checked_add(amount).ok_or(Error::<T>::NumOverflow)?;
+        PendingFungibleLinks::<T>::insert(parent, (&who, fungible_collection_id), pending);
+
+        Self::deposit_event(RawEvent::FungibleLinkRequested(who, fungible_collection_id, parent.0, parent.1, amount));
+
+        Ok(())
+    }
+
+    /// Whether NFTs of `child_collection_id` can be linked to NFTs of `parent_collection_id`.
+    ///
+    /// Any NFT can be linked to a collection without slots, a base only accepts its equippable collections.
+    fn accepts_child(parent_collection_id: &T::Hash, child_collection_id: &T::Hash) -> bool {
+        let mut slots = Slots::<T>::iter_prefix_values(parent_collection_id).peekable();
+        slots.peek().is_none() || slots.any(|slot| slot.equippable.contains(child_collection_id))
+    }
+
+    /// Remove the link of a child NFT without children to its parent.
+    fn detach(child: (T::Hash, u128)) {
+        Self::unequip_token(child);
+        RootOf::<T>::remove(child);
+        if ChildToParent::<T>::contains_key(child) {
+            let parent = ChildToParent::<T>::take(child);
+            ParentToChild::<T>::remove(parent, child);
+        }
+    }
+
+    fn has_children(token: (T::Hash, u128)) -> bool {
+        ParentToChild::<T>::iter_prefix(token).next().is_some() || ParentBalance::<T>::iter_prefix(token).next().is_some()
+    }
+
+    /// Transfer to `who`, or burn, at most `max_steps` NFTs and FT balances linked below `token`, leaves first.
+    /// Return whether nothing is linked to `token` anymore.
+    fn clear_subtree(who: &T::AccountId, token: (T::Hash, u128), max_steps: u32, burn: bool) -> Result<bool, DispatchError> {
+        for _ in 0..max_steps {
+            let mut leaf = token;
+            while let Some(child) = ParentToChild::<T>::iter_prefix(leaf).next().map(|(child, _)| child) {
+                leaf = child;
+            }
+
+            let fungible = ParentBalance::<T>::iter_prefix(leaf).next();
+            if let Some((fungible_collection_id, amount)) = fungible {
+                ParentBalance::<T>::remove(leaf, fungible_collection_id);
+                if burn {
+                    T::NFT::_burn_fungible(Self::account_id(), fungible_collection_id, amount)?;
+                } else {
+                    ensure!(!T::NFT::is_frozen(&fungible_collection_id, who), Error::<T>::Frozen);
+                    T::NFT::_transfer_fungible(Self::account_id(), who.clone(), fungible_collection_id, amount)?;
+                }
+            } else if leaf != token {
+                Self::detach(leaf);
+                if burn {
+                    LinkAllowlist::<T>::remove_prefix(leaf);
+                    T::NFT::_burn_non_fungible(Self::account_id(), leaf.0, leaf.1, 1)?;
+                } else {
+                    ensure!(!T::NFT::is_frozen(&leaf.0, who), Error::<T>::Frozen);
+                    T::NFT::_transfer_non_fungible(Self::account_id(), who.clone(), leaf.0, leaf.1, 1)?;
+                }
+            } else {
+                return Ok(true);
+            }
+        }
+        Ok(!Self::has_children(token))
+    }
+
+    /// Take a child NFT out of its slot, if it is equipped.
+    fn unequip_token(child: (T::Hash, u128)) {
+        if let Some(slot) = EquippedSlot::<T>::take(child) {
+            let parent = Self::child_to_parent(child);
+            SlotUsage::<T>::mutate_exists(parent, &slot, |usage| {
+                *usage = usage.and_then(|usage| usage.checked_sub(1)).filter(|usage| *usage > 0);
+            });
+        }
+    }
+
     /// Find the root owner of this NFT.
     fn find_root_owner(
         child_collection_id: T::Hash,
         child_token_id: u128,
     ) -> Result<T::AccountId, DispatchError> {
         // root token: owner isn't equal with pallet account
-        // if can't find parent token in pallet_nft, it may be burned.
         let token = T::NFT::get_nft_token(child_collection_id, child_token_id);
         if token.owner != Self::account_id() {
-            Ok(token.owner)
-        } else {
-            let (parent_collection_id, parent_token_id) =
-                Self::child_to_parent((child_collection_id, child_token_id));
-            ensure!(
-                T::NFT::token_exist(parent_collection_id, parent_token_id),
-                Error::<T>::RootTokenNotFound
-            );
-            Self::find_root_owner(parent_collection_id, parent_token_id)
+            return Ok(token.owner);
         }
+
+        // a pending NFT has no root, nor the NFTs linked below it.
+        // if can't find root token in pallet_nft, it may be burned.
+        let (root_collection_id, root_token_id) =
+            Self::root_of((child_collection_id, child_token_id)).ok_or(Error::<T>::RootTokenNotFound)?;
+        ensure!(
+            T::NFT::token_exist(root_collection_id, root_token_id),
+            Error::<T>::RootTokenNotFound
+        );
+        let root_token = T::NFT::get_nft_token(root_collection_id, root_token_id);
+        ensure!(root_token.owner != Self::account_id(), Error::<T>::RootTokenNotFound);
+
+        Ok(root_token.owner)
     }
 
-    /// Recursively check whether this NFT is an ancestor NFT.
-    fn is_ancestor(
+    /// Check whether this NFT is an ancestor NFT.
+    pub fn is_ancestor(
         maybe_ancestor_token: (T::Hash, u128),
         maybe_descendant_token: (T::Hash, u128),
     ) -> Result<bool, DispatchError> {
-        let (ancestor_collection_id, ancestor_token_id) = maybe_ancestor_token;
-        let (descendant_collection_id, descendant_token_id) = maybe_descendant_token;
+        Ok(Self::ancestors(maybe_descendant_token)?.contains(&maybe_ancestor_token))
+    }
 
-        // if can't find descendant token's parent, it walks to the end and the token may be a root token.
-        let have_parent =
-            ChildToParent::<T>::contains_key((descendant_collection_id, descendant_token_id));
+    /// The NFTs above this NFT from its parent up to its root, at most `MaxDepth`.
+    fn ancestors(token: (T::Hash, u128)) -> Result<Vec<(T::Hash, u128)>, DispatchError> {
+        let mut ancestors = Vec::new();
+        let mut token = token;
 
-        if have_parent {
-            let (parent_collection_id, parent_token_id) =
-                Self::child_to_parent((descendant_collection_id, descendant_token_id));
+        // if can't find token's parent, it walks to the end and the token may be a root token.
+        while ChildToParent::<T>::contains_key(token) {
+            ensure!(ancestors.len() < T::MaxDepth::get() as usize, Error::<T>::TooDeep);
+            token = Self::child_to_parent(token);
 
             // if parent token not in pallet_nft, it may be burned.
             ensure!(
-                T::NFT::token_exist(parent_collection_id, parent_token_id),
+                T::NFT::token_exist(token.0, token.1),
                 Error::<T>::RootTokenNotFound
             );
+            ancestors.push(token);
+        }
+
+        Ok(ancestors)
+    }
 
-            // check whether token's parent equal with ancestor token
-            let is_equal = parent_collection_id == ancestor_collection_id
-                && parent_token_id == ancestor_token_id;
+    /// The NFTs linked below this NFT, at most `MaxDescendants`, and the depth of the deepest one.
+    fn bounded_descendants(token: (T::Hash, u128)) -> Result<(Vec<(T::Hash, u128)>, u32), DispatchError> {
+        let mut descendants = Vec::new();
+        let mut height = 0;
+        let mut pending = sp_std::vec![(token, 0)];
+        while let Some((parent, depth)) = pending.pop() {
+            height = height.max(depth);
+            for (child, _) in ParentToChild::<T>::iter_prefix(parent) {
+                ensure!(descendants.len() < T::MaxDescendants::get() as usize, Error::<T>::TooManyDescendants);
+                descendants.push(child);
+                pending.push((child, depth + 1));
+            }
+        }
+        Ok((descendants, height))
+    }
 
-            if is_equal {
-                Ok(true)
-            } else {
-                Self::is_ancestor(
-                    (ancestor_collection_id, ancestor_token_id),
-                    (parent_collection_id, parent_token_id),
-                )
+    /// Check that `child` can be linked below `parent`, return the root of `parent` and the NFTs linked below `child`.
+    fn prepare_link(child: (T::Hash, u128), parent: (T::Hash, u128)) -> Result<((T::Hash, u128), Vec<(T::Hash, u128)>), DispatchError> {
+        let ancestors = Self::ancestors(parent)?;
+        ensure!(
+            child != parent && !ancestors.contains(&child),
+            Error::<T>::CanNotLinkAncestorToDescendant
+        );
+
+        let (descendants, height) = Self::bounded_descendants(child)?;
+        ensure!(
+            (ancestors.len() as u32).saturating_add(1).saturating_add(height) <= T::MaxDepth::get(),
+            Error::<T>::TooDeep
+        );
+
+        Ok((ancestors.last().copied().unwrap_or(parent), descendants))
+    }
+
+    /// Point `token` to `root`, and the NFTs linked below it to `root`, or to `token` when it has no root.
+    fn set_root(token: (T::Hash, u128), root: Option<(T::Hash, u128)>, descendants: &[(T::Hash, u128)]) {
+        match root {
+            Some(root) => RootOf::<T>::insert(token, root),
+            None => RootOf::<T>::remove(token),
+        }
+        let root = root.unwrap_or(token);
+        for descendant in descendants {
+            RootOf::<T>::insert(descendant, root);
+        }
+    }
+
+    /// Point existing linked NFTs to their root NFT.
+    fn migrate_to_v1() -> Weight {
+        let children: Vec<(T::Hash, u128)> = ChildToParent::<T>::iter().map(|(child, _)| child).collect();
+
+        let mut reads = children.len() as Weight;
+        let writes = children.len() as Weight;
+
+        for child in children.iter() {
+            let mut root = Self::child_to_parent(child);
+            while ChildToParent::<T>::contains_key(root) {
+                root = Self::child_to_parent(root);
+                reads += 1;
             }
-        } else {
-            Ok(false)
+            RootOf::<T>::insert(child, root);
         }
+
+        StorageVersion::put(1);
+
+        T::DbWeight::get().reads_writes(reads, writes.saturating_add(1))
     }
 }
@@ -19,7 +19,8 @@ frame_support::construct_runtime!(
         UncheckedExtrinsic = UncheckedExtrinsic,
     {
         System: frame_system::{Module, Call, Config, Storage, Event<T>},
-        CollectionModule: pallet_collection::{Module, Call, Storage, Event<T>},
+        Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
+    CollectionModule: pallet_collection::{Module, Call, Storage, Event<T>},
         NFTModule: pallet_nft::{Module, Call, Storage, Event<T>},
         GraphModule: pallet_graph::{Module, Call, Storage, Event<T>},
     }
@@ -48,27 +49,65 @@ impl system::Config for Test {
     type BlockHashCount = BlockHashCount;
     type Version = ();
     type PalletInfo = PalletInfo;
-    type AccountData = ();
+    type AccountData = pallet_balances::AccountData<u64>;
     type OnNewAccount = ();
     type OnKilledAccount = ();
     type SystemWeightInfo = ();
     type SS58Prefix = SS58Prefix;
 }
 
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+    pub const CollectionDeposit: u64 = 0;
+    pub const StringLimit: u32 = 50;
+    pub const MaxBatchTransfers: u32 = 10;
+    pub const MaxEquippables: u32 = 2;
+    pub const MaxDepth: u32 = 6;
+    pub const MaxDescendants: u32 = 5;
+    pub const MaxTokenLocks: u32 = 3;
+}
+
+impl pallet_balances::Config for Test {
+    type MaxLocks = ();
+    type Balance = u64;
+    type Event = Event;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
+
 impl pallet_collection::Config for Test {
     type Event = Event;
     type RandomnessSource = TestRandomness;
+    type Currency = Balances;
+    type CollectionDeposit = CollectionDeposit;
+    type StringLimit = StringLimit;
 }
 
 impl pallet_nft::Config for Test {
     type Event = Event;
     type Collection = CollectionModule;
+    type OnTransfer = ();
+    type OnMint = ();
+    type OnBurn = GraphModule;
+    type MaxBatchTransfers = MaxBatchTransfers;
+    type Currency = Balances;
+    type Signature = sp_runtime::testing::TestSignature;
+    type Signer = sp_runtime::testing::UintAuthorityId;
+    type RangeKeepers = GraphModule;
+    type ForceOrigin = frame_system::EnsureRoot<u64>;
+    type MaxTokenLocks = MaxTokenLocks;
 }
 
 impl pallet_graph::Config for Test {
     type Event = Event;
     type Collection = CollectionModule;
     type NFT = NFTModule;
+    type StringLimit = StringLimit;
+    type MaxEquippables = MaxEquippables;
+    type MaxDepth = MaxDepth;
+    type MaxDescendants = MaxDescendants;
 }
 
 // Build genesis storage according to the mock runtime.
@@ -1,4 +1,4 @@
-use crate::{mock::*, Error};
+use crate::{mock::*, ChildToParent, Error, GraphInterface};
 use frame_support::{assert_noop, assert_ok};
 use sp_core::H256;
 use pallet_collection::CollectionInterface;
@@ -126,6 +126,7 @@ fn link_non_fungible() {
             parent_collection_id,
             parent_token_id
         ));
+        assert_eq!(GraphModule::root_of((child_collection_id, 5)), Some((parent_collection_id, parent_token_id)));
     });
 }
 
@@ -288,6 +289,74 @@ fn recover() {
     });
 }
 
+#[test]
+fn link_and_recover_frozen() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        let mint_amount = 10;
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let child_collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let parent_collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        assert_ok!(NFTModule::mint_non_fungible(
+            alice.clone(),
+            alice_address,
+            child_collection_id,
+            vec![2, 3, 3],
+            mint_amount
+        ));
+
+        assert_ok!(NFTModule::mint_non_fungible(
+            alice.clone(),
+            alice_address,
+            parent_collection_id,
+            vec![2, 3, 3],
+            mint_amount
+        ));
+
+        assert_ok!(GraphModule::link_non_fungible(
+            alice.clone(),
+            child_collection_id,
+            0,
+            parent_collection_id,
+            0
+        ));
+        assert_ok!(GraphModule::link_non_fungible(
+            alice.clone(),
+            child_collection_id,
+            1,
+            parent_collection_id,
+            0
+        ));
+
+        assert_ok!(NFTModule::freeze_account(Origin::root(), child_collection_id, alice_address));
+
+        assert_noop!(
+            GraphModule::link_non_fungible(alice.clone(), child_collection_id, 1, child_collection_id, 0),
+            Error::<Test>::Frozen
+        );
+        assert_noop!(
+            GraphModule::link_non_fungible(alice.clone(), child_collection_id, 2, parent_collection_id, 0),
+            pallet_nft::Error::<Test>::Frozen
+        );
+        assert_noop!(
+            GraphModule::recover_non_fungible(alice.clone(), child_collection_id, 1),
+            Error::<Test>::Frozen
+        );
+
+        assert_ok!(NFTModule::thaw_account(Origin::root(), child_collection_id, alice_address));
+        assert_ok!(GraphModule::recover_non_fungible(alice, child_collection_id, 1));
+    });
+}
+
 #[test]
 fn recover_fungible() {
     new_test_ext().execute_with(|| {
@@ -380,3 +449,475 @@ fn recover_fungible() {
         ));
     });
 }
+
+#[test]
+fn descendants() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        assert_ok!(NFTModule::mint_non_fungible(
+            alice.clone(),
+            alice_address,
+            collection_id,
+            vec![2, 3, 3],
+            4
+        ));
+
+        // 0 <- 1 <- 2, 0 <- 3
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), collection_id, 1, collection_id, 0));
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), collection_id, 2, collection_id, 1));
+        assert_ok!(GraphModule::link_non_fungible(alice, collection_id, 3, collection_id, 0));
+
+        assert_eq!(<GraphModule as GraphInterface<_, _>>::is_linked(&collection_id, 0), false);
+        assert_eq!(<GraphModule as GraphInterface<_, _>>::is_linked(&collection_id, 2), true);
+
+        let mut descendants = <GraphModule as GraphInterface<_, _>>::descendants(&collection_id, 0);
+        descendants.sort();
+        assert_eq!(descendants, vec![(collection_id, 1), (collection_id, 2), (collection_id, 3)]);
+        assert_eq!(<GraphModule as GraphInterface<_, _>>::descendants(&collection_id, 2), vec![]);
+
+        let mut subtree = GraphModule::subtree(collection_id, 0).unwrap().non_fungible;
+        subtree.sort();
+        assert_eq!(subtree, vec![
+            ((collection_id, 1), (collection_id, 0)),
+            ((collection_id, 2), (collection_id, 1)),
+            ((collection_id, 3), (collection_id, 0)),
+        ]);
+
+        let subtree_hash = <GraphModule as GraphInterface<_, _>>::subtree_hash(&collection_id, 0).unwrap();
+        assert!(subtree_hash.is_some());
+        assert_eq!(<GraphModule as GraphInterface<_, _>>::subtree_hash(&collection_id, 2), Ok(None));
+
+        assert_ok!(GraphModule::recover_non_fungible(Origin::signed(alice_address), collection_id, 2));
+        assert_ne!(<GraphModule as GraphInterface<_, _>>::subtree_hash(&collection_id, 0), Ok(subtree_hash));
+    });
+}
+
+#[test]
+fn subtree_is_bounded() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        assert_ok!(NFTModule::mint_non_fungible(
+            alice.clone(),
+            alice_address,
+            collection_id,
+            vec![2, 3, 3],
+            7
+        ));
+
+        // 0 <- 1, 0 <- 2, ..., 0 <- 6
+        for token_id in 1..=6 {
+            assert_ok!(GraphModule::link_non_fungible(alice.clone(), collection_id, token_id, collection_id, 0));
+        }
+
+        // the subtree is never truncated
+        assert_eq!(<GraphModule as GraphInterface<_, _>>::descendants(&collection_id, 0).len(), 6);
+        assert_eq!(GraphModule::subtree(collection_id, 0), Err(Error::<Test>::TooManyDescendants.into()));
+        assert_noop!(
+            <GraphModule as GraphInterface<_, _>>::subtree_hash(&collection_id, 0),
+            Error::<Test>::TooManyDescendants
+        );
+
+        assert_ok!(GraphModule::recover_non_fungible(alice, collection_id, 6));
+        assert_eq!(GraphModule::subtree(collection_id, 0).unwrap().non_fungible.len(), MaxDescendants::get() as usize);
+    });
+}
+
+#[test]
+fn equip_success() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let base_collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let part_collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        assert_ok!(NFTModule::mint_non_fungible(alice.clone(), alice_address, base_collection_id, vec![2, 3, 3], 1));
+        assert_ok!(NFTModule::mint_non_fungible(alice.clone(), alice_address, part_collection_id, vec![2, 3, 3], 3));
+
+        assert_ok!(GraphModule::set_slot(alice.clone(), base_collection_id, b"hand".to_vec(), vec![part_collection_id], 2));
+        assert_eq!(GraphModule::slot(base_collection_id, b"hand".to_vec()).unwrap().max, 2);
+
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), part_collection_id, 0, base_collection_id, 0));
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), part_collection_id, 1, base_collection_id, 0));
+        assert_ok!(GraphModule::equip(alice.clone(), part_collection_id, 0, b"hand".to_vec()));
+        assert_ok!(GraphModule::equip(alice.clone(), part_collection_id, 1, b"hand".to_vec()));
+
+        let mut layout = GraphModule::equipped(base_collection_id, 0);
+        layout.sort();
+        let mut expected = vec![(b"hand".to_vec(), (part_collection_id, 0)), (b"hand".to_vec(), (part_collection_id, 1))];
+        expected.sort();
+        assert_eq!(layout, expected);
+        assert_eq!(GraphModule::slot_usage((base_collection_id, 0), b"hand".to_vec()), 2);
+
+        assert_ok!(GraphModule::unequip(alice.clone(), part_collection_id, 0));
+        assert_eq!(GraphModule::equipped_slot((part_collection_id, 0)), None);
+        assert_eq!(GraphModule::child_to_parent((part_collection_id, 0)), (base_collection_id, 0));
+
+        // recovering an equipped child frees its slot
+        assert_ok!(GraphModule::recover_non_fungible(alice, part_collection_id, 1));
+        assert_eq!(GraphModule::slot_usage((base_collection_id, 0), b"hand".to_vec()), 0);
+        assert_eq!(GraphModule::equipped(base_collection_id, 0), vec![]);
+    });
+}
+
+#[test]
+fn equip_failed() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(2);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let base_collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let part_collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let other_collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        assert_ok!(NFTModule::mint_non_fungible(alice.clone(), alice_address, base_collection_id, vec![2, 3, 3], 1));
+        assert_ok!(NFTModule::mint_non_fungible(alice.clone(), alice_address, part_collection_id, vec![2, 3, 3], 2));
+        assert_ok!(NFTModule::mint_non_fungible(alice.clone(), alice_address, other_collection_id, vec![2, 3, 3], 1));
+
+        assert_noop!(
+            GraphModule::set_slot(bob, base_collection_id, b"hand".to_vec(), vec![part_collection_id], 1),
+            Error::<Test>::PermissionDenied
+        );
+        assert_noop!(
+            GraphModule::set_slot(alice.clone(), base_collection_id, vec![], vec![part_collection_id], 1),
+            Error::<Test>::BadSlotName
+        );
+        assert_noop!(
+            GraphModule::set_slot(alice.clone(), base_collection_id, b"hand".to_vec(), vec![part_collection_id; 3], 1),
+            Error::<Test>::TooManyEquippables
+        );
+        assert_ok!(GraphModule::set_slot(alice.clone(), base_collection_id, b"hand".to_vec(), vec![part_collection_id], 1));
+
+        // a base only accepts equippable collections
+        assert_noop!(
+            GraphModule::link_non_fungible(alice.clone(), other_collection_id, 0, base_collection_id, 0),
+            Error::<Test>::NotEquippable
+        );
+
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), part_collection_id, 0, base_collection_id, 0));
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), part_collection_id, 1, base_collection_id, 0));
+        assert_noop!(
+            GraphModule::equip(alice.clone(), part_collection_id, 0, b"head".to_vec()),
+            Error::<Test>::SlotNotFound
+        );
+        assert_noop!(
+            GraphModule::equip(Origin::signed(2), part_collection_id, 0, b"hand".to_vec()),
+            Error::<Test>::PermissionDenied
+        );
+        assert_ok!(GraphModule::equip(alice.clone(), part_collection_id, 0, b"hand".to_vec()));
+        assert_noop!(
+            GraphModule::equip(alice.clone(), part_collection_id, 0, b"hand".to_vec()),
+            Error::<Test>::AlreadyEquipped
+        );
+        assert_noop!(
+            GraphModule::equip(alice.clone(), part_collection_id, 1, b"hand".to_vec()),
+            Error::<Test>::SlotFull
+        );
+        assert_noop!(
+            GraphModule::unequip(alice, part_collection_id, 1),
+            Error::<Test>::NotEquipped
+        );
+    });
+}
+
+#[test]
+fn link_consent() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        let bob_address = 2;
+        let bob = Origin::signed(bob_address);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let parent_collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        CollectionModule::create_collection(bob.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let child_collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        CollectionModule::create_collection(bob.clone(), vec![2, 3, 3], true).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let fungible_collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        assert_ok!(NFTModule::mint_non_fungible(alice.clone(), alice_address, parent_collection_id, vec![2, 3, 3], 1));
+        assert_ok!(NFTModule::mint_non_fungible(bob.clone(), bob_address, child_collection_id, vec![2, 3, 3], 3));
+        assert_ok!(NFTModule::mint_fungible(bob.clone(), bob_address, fungible_collection_id, 100));
+
+        // bob's NFT waits for alice
+        assert_ok!(GraphModule::link_non_fungible(bob.clone(), child_collection_id, 0, parent_collection_id, 0));
+        assert_eq!(GraphModule::pending_link((child_collection_id, 0)).unwrap().sender, bob_address);
+        assert_eq!(ChildToParent::<Test>::contains_key((child_collection_id, 0)), false);
+        assert_noop!(
+            GraphModule::accept_link(bob.clone(), child_collection_id, 0),
+            Error::<Test>::PermissionDenied
+        );
+        assert_ok!(GraphModule::accept_link(alice.clone(), child_collection_id, 0));
+        assert_eq!(GraphModule::child_to_parent((child_collection_id, 0)), (parent_collection_id, 0));
+        assert_eq!(GraphModule::pending_link((child_collection_id, 0)), None);
+
+        // rejected NFTs return to the sender
+        assert_ok!(GraphModule::link_non_fungible(bob.clone(), child_collection_id, 1, parent_collection_id, 0));
+        assert_ok!(GraphModule::reject_link(alice.clone(), child_collection_id, 1));
+        assert_eq!(NFTModule::tokens(child_collection_id, 1).owner, bob_address);
+        assert_noop!(
+            GraphModule::reject_link(alice.clone(), child_collection_id, 1),
+            Error::<Test>::LinkRequestNotFound
+        );
+
+        // FTs wait too
+        assert_ok!(GraphModule::link_fungible(bob.clone(), None, None, fungible_collection_id, parent_collection_id, 0, 10));
+        assert_eq!(GraphModule::pending_fungible_link((parent_collection_id, 0), (bob_address, fungible_collection_id)), 10);
+        assert_eq!(GraphModule::parent_balance((parent_collection_id, 0), fungible_collection_id), 0);
+        assert_ok!(GraphModule::accept_fungible_link(alice.clone(), parent_collection_id, 0, bob_address, fungible_collection_id));
+        assert_eq!(GraphModule::parent_balance((parent_collection_id, 0), fungible_collection_id), 10);
+
+        assert_ok!(GraphModule::link_fungible(bob.clone(), None, None, fungible_collection_id, parent_collection_id, 0, 5));
+        assert_ok!(GraphModule::reject_fungible_link(bob.clone(), parent_collection_id, 0, bob_address, fungible_collection_id));
+        assert_eq!(NFTModule::address_balances((fungible_collection_id, bob_address)), 90);
+
+        // allowlisted collections are linked at once
+        assert_noop!(
+            GraphModule::set_link_allowlist(bob.clone(), parent_collection_id, 0, child_collection_id, true),
+            Error::<Test>::PermissionDenied
+        );
+        assert_ok!(GraphModule::set_link_allowlist(alice, parent_collection_id, 0, child_collection_id, true));
+        assert_ok!(GraphModule::link_non_fungible(bob, child_collection_id, 2, parent_collection_id, 0));
+        assert_eq!(GraphModule::child_to_parent((child_collection_id, 2)), (parent_collection_id, 0));
+    });
+}
+
+#[test]
+fn unlink_all() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(2);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], true).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let fungible_collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        assert_ok!(NFTModule::mint_fungible(alice.clone(), alice_address, fungible_collection_id, 10));
+        assert_ok!(NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 4));
+
+        // 0 <- 1 <- 2, 0 <- 3, FTs linked to 0 and 2
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), collection_id, 1, collection_id, 0));
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), collection_id, 2, collection_id, 1));
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), collection_id, 3, collection_id, 0));
+        assert_ok!(GraphModule::link_fungible(alice.clone(), None, None, fungible_collection_id, collection_id, 2, 4));
+        assert_ok!(GraphModule::link_fungible(alice.clone(), None, None, fungible_collection_id, collection_id, 0, 1));
+
+        assert_noop!(
+            NFTModule::burn_non_fungible(alice.clone(), collection_id, 0, 1),
+            Error::<Test>::ParentTokenHasChildren
+        );
+        assert_noop!(
+            GraphModule::unlink_all(bob, collection_id, 0, 10),
+            Error::<Test>::PermissionDenied
+        );
+
+        // leaves first, 1 can't be recovered before 2 and its FTs
+        assert_ok!(GraphModule::unlink_all(alice.clone(), collection_id, 0, 2));
+        assert_eq!(ChildToParent::<Test>::contains_key((collection_id, 1)), true);
+        assert!(<GraphModule as GraphInterface<_, _>>::subtree_hash(&collection_id, 0).unwrap().is_some());
+
+        assert_ok!(GraphModule::unlink_all(alice.clone(), collection_id, 0, 10));
+        assert_eq!(NFTModule::address_balances((fungible_collection_id, alice_address)), 10);
+        assert_eq!(NFTModule::tokens(collection_id, 1).owner, alice_address);
+        assert_eq!(NFTModule::tokens(collection_id, 3).owner, alice_address);
+        assert_eq!(<GraphModule as GraphInterface<_, _>>::subtree_hash(&collection_id, 0), Ok(None));
+
+        assert_ok!(NFTModule::burn_non_fungible(alice, collection_id, 0, 1));
+    });
+}
+
+#[test]
+fn burn_tree() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(2);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], true).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let fungible_collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        assert_ok!(NFTModule::mint_fungible(alice.clone(), alice_address, fungible_collection_id, 10));
+        assert_ok!(NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 4));
+
+        // 0 <- 1 <- 2, 0 <- 3, FTs linked to 0 and 2
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), collection_id, 1, collection_id, 0));
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), collection_id, 2, collection_id, 1));
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), collection_id, 3, collection_id, 0));
+        assert_ok!(GraphModule::link_fungible(alice.clone(), None, None, fungible_collection_id, collection_id, 2, 4));
+        assert_ok!(GraphModule::link_fungible(alice.clone(), None, None, fungible_collection_id, collection_id, 0, 1));
+
+        assert_noop!(
+            GraphModule::burn_tree(bob, collection_id, 0, 10),
+            Error::<Test>::PermissionDenied
+        );
+
+        // a child is burned with its own subtree
+        assert_ok!(GraphModule::burn_tree(alice.clone(), collection_id, 1, 10));
+        assert_eq!(pallet_nft::Tokens::<Test>::contains_key(collection_id, 1), false);
+        assert_eq!(pallet_nft::Tokens::<Test>::contains_key(collection_id, 2), false);
+        assert_eq!(NFTModule::address_balances((fungible_collection_id, GraphModule::account_id())), 1);
+        assert_eq!(<GraphModule as GraphInterface<_, _>>::descendants(&collection_id, 0), vec![(collection_id, 3)]);
+
+        assert_ok!(GraphModule::burn_tree(alice.clone(), collection_id, 0, 1));
+        assert_eq!(pallet_nft::Tokens::<Test>::contains_key(collection_id, 0), true);
+        assert_eq!(pallet_nft::Tokens::<Test>::contains_key(collection_id, 3), false);
+
+        assert_ok!(GraphModule::burn_tree(alice, collection_id, 0, 10));
+        assert_eq!(pallet_nft::Tokens::<Test>::contains_key(collection_id, 0), false);
+        assert_eq!(NFTModule::address_balances((fungible_collection_id, GraphModule::account_id())), 0);
+        assert_eq!(NFTModule::address_balances((fungible_collection_id, alice_address)), 5);
+    });
+}
+
+#[test]
+fn link_limits() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        assert_ok!(NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 17));
+
+        // 0 <- 1 <- 2 <- 3 <- 4 <- 5 <- 6, MaxDepth is 6
+        for token_id in 1..7 {
+            assert_ok!(GraphModule::link_non_fungible(alice.clone(), collection_id, token_id, collection_id, token_id - 1));
+        }
+        assert_eq!(GraphModule::root_of((collection_id, 6)), Some((collection_id, 0)));
+        assert_eq!(GraphModule::find_root_owner(collection_id, 6), Ok(alice_address));
+        assert_noop!(
+            GraphModule::link_non_fungible(alice.clone(), collection_id, 7, collection_id, 6),
+            Error::<Test>::TooDeep
+        );
+
+        // 8 <- 9, moving 8 below 5 makes 9 too deep
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), collection_id, 9, collection_id, 8));
+        assert_noop!(
+            GraphModule::link_non_fungible(alice.clone(), collection_id, 8, collection_id, 5),
+            Error::<Test>::TooDeep
+        );
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), collection_id, 8, collection_id, 4));
+        assert_eq!(GraphModule::root_of((collection_id, 9)), Some((collection_id, 0)));
+
+        // 10 <- 11..16, MaxDescendants is 5
+        for token_id in 11..17 {
+            assert_ok!(GraphModule::link_non_fungible(alice.clone(), collection_id, token_id, collection_id, 10));
+        }
+        assert_noop!(
+            GraphModule::link_non_fungible(alice.clone(), collection_id, 10, collection_id, 0),
+            Error::<Test>::TooManyDescendants
+        );
+
+        // leaves are unlinked one by one
+        assert_ok!(GraphModule::recover_non_fungible(alice.clone(), collection_id, 11));
+        assert_eq!(GraphModule::root_of((collection_id, 11)), None);
+        assert_ok!(GraphModule::link_non_fungible(alice, collection_id, 10, collection_id, 0));
+        assert_eq!(GraphModule::root_of((collection_id, 15)), Some((collection_id, 0)));
+    });
+}
+
+#[test]
+fn parent_with_fungible_keeps_range() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(bob_address);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], true).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let fungible_collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        assert_ok!(NFTModule::mint_fungible(alice.clone(), alice_address, fungible_collection_id, 10));
+        assert_ok!(NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 2));
+        assert_ok!(NFTModule::transfer_non_fungible(alice.clone(), bob_address, collection_id, 1, 1));
+        assert_ok!(GraphModule::link_fungible(alice.clone(), None, None, fungible_collection_id, collection_id, 0, 4));
+
+        // 1 comes back but isn't merged into the parent 0
+        assert_ok!(NFTModule::transfer_non_fungible(bob, alice_address, collection_id, 1, 1));
+        assert_eq!(NFTModule::tokens(collection_id, 0).end_idx, 0);
+        assert_eq!(NFTModule::token_exist(collection_id, 1), true);
+
+        assert_ok!(GraphModule::recover_fungible(alice, collection_id, 0, fungible_collection_id, 4));
+        assert_eq!(NFTModule::address_balances((fungible_collection_id, alice_address)), 10);
+    });
+}
+
+#[test]
+fn destroy_collection_with_children() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let child_collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        assert_ok!(NFTModule::mint_non_fungible(alice.clone(), alice_address, child_collection_id, vec![2, 3, 3], 1));
+        assert_ok!(NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 1));
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), child_collection_id, 0, collection_id, 0));
+
+        let witness = pallet_nft::DestroyWitness { ranges: 1, items: 0 };
+        assert_noop!(
+            NFTModule::destroy_collection(alice.clone(), collection_id, witness),
+            Error::<Test>::ParentTokenHasChildren
+        );
+
+        assert_ok!(GraphModule::unlink_all(alice.clone(), collection_id, 0, 1));
+        assert_ok!(NFTModule::destroy_collection(alice, collection_id, witness));
+        assert_eq!(CollectionModule::collection_exist(collection_id), false);
+    });
+}
@@ -23,6 +23,42 @@
 //! * `transfer_non_fungible` - Transfer one or a batch of NFTs to another account
 //! * `burn_fungible` - Destroy some FTs by owner
 //! * `burn_non_fungible` - Destroy one or a batch of NFTs NFTs by owner
+//! * `destroy_collection` - Destroy a collection and clean up its tokens in batches
+//! * `batch_transfer` - Transfer NFTs or FTs of several collections to several accounts at once
+//! * `merge_ranges` - Merge a batch of NFTs with the following batches of the same owner and uri
+//! * `lock_non_fungible` - Lock one or a batch of NFTs so that they can't be transferred
+//! * `unlock_non_fungible` - Remove a lock set by `lock_non_fungible`
+//! * `lock_fungible` - Lock some FTs so that they can't be transferred
+//! * `unlock_fungible` - Remove a lock set by `lock_fungible`
+//! * `mint_with_voucher` - Mint NFTs with a voucher signed by the collection owner or a minter and pay the price
+//! * `cancel_voucher` - Stop a voucher from being used
+//! * `add_minter` - Allow an account to sign vouchers of a collection
+//! * `remove_minter` - Remove a minter of a collection
+//! * `set_token_uri` - Change the uri of a NFT
+//! * `add_metadata_updater` - Allow an account to change the uri of NFTs of a collection
+//! * `remove_metadata_updater` - Remove a metadata updater of a collection
+//! * `freeze_metadata` - Stop the uri of NFTs of a collection from being changed forever
+//! * `freeze_collection` - Stop all tokens of a collection from being transferred or minted, by `ForceOrigin`
+//! * `thaw_collection` - Undo `freeze_collection`, by `ForceOrigin`
+//! * `freeze_account` - Stop an account from transferring its tokens of a collection, by `ForceOrigin`
+//! * `thaw_account` - Undo `freeze_account`, by `ForceOrigin`
+//! * `force_transfer` - Move tokens of an account with a reason code, by `ForceOrigin`
+//!
+//! Tokens of a non-transferable collection and locked tokens can't be transferred, but can be burned.
+//! Other pallets can lock tokens through `NFTInterface` with their own `LockIdentifier`.
+//!
+//! Other pallets can react to token movements through `OnTokenMint`, `OnTokenTransfer` and
+//! `OnTokenBurn`, which are called before the state is changed and can veto it by returning an error.
+//!
+//! When NFTs are transferred, the received batch is merged with the adjacent batches of the receiver
+//! if they have the same uri. Batches that other pallets refer to by start_idx are kept as they are,
+//! see `KeepsTokenRanges`.
+//!
+//! NFTs and FTs can be minted in the chain spec with the `non_fungible_tokens` and `fungible_tokens`
+//! genesis config, after the collections of pallet-collection are created.
+//!
+//! Third-party pallets can use tokens through the FRAME `fungibles` and `nonfungibles` traits,
+//! see the `tokens` module.
 //! 
 //! [`Call`]: ./enum.Call.html
 //! [`Config`]: ./trait.Config.html
@@ -34,11 +70,19 @@ use frame_support::{
     decl_error, decl_event, decl_module, decl_storage,
     dispatch::{DispatchError, DispatchResult},
     ensure,
+    storage::StoragePrefixedMap,
+    traits::{Currency, EnsureOrigin, ExistenceRequirement, Get, LockIdentifier},
+    transactional,
+    weights::Weight,
+    Parameter,
 };
 use frame_system::ensure_signed;
 use pallet_collection::{CollectionInfo, CollectionInterface, TokenType};
+use sp_runtime::traits::{CheckedMul, Hash, IdentifyAccount, SaturatedConversion, Verify, Zero};
 use sp_std::vec::Vec;
 
+pub mod tokens;
+
 #[cfg(test)]
 mod mock;
 
@@ -53,10 +97,170 @@ pub struct TokenInfo<AccountId> {
     pub uri: Vec<u8>,
 }
 
+/// Witness data for `destroy_collection`.
+#[derive(Encode, Decode, Default, Clone, Copy, PartialEq)]
+pub struct DestroyWitness {
+    /// The maximum number of token ranges to remove in this call.
+    pub ranges: u32,
+    /// The maximum number of other entries of the collection, such as locks, minters,
+    /// metadata updaters and frozen accounts, to remove in this call.
+    pub items: u32,
+}
+
+/// A transfer in `batch_transfer`.
+#[derive(Encode, Decode, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct BatchTransferItem<AccountId, Hash> {
+    pub receiver: AccountId,
+    pub collection_id: Hash,
+    /// The index of the token or a batch of tokens to be transferred, ignored for FTs.
+    pub start_idx: u128,
+    pub amount: u128,
+}
+
+/// Allows the holder of a signature to mint NFTs of a collection without the owner paying to mint them up front.
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct MintVoucher<Hash, Balance, BlockNumber> {
+    pub collection_id: Hash,
+    pub uri: Vec<u8>,
+    /// The maximum number of NFTs that can be minted with the voucher.
+    pub max_amount: u128,
+    /// The price of each NFT, paid to the collection owner.
+    pub price: Balance,
+    /// The last block in which the voucher can be used.
+    pub expiry: BlockNumber,
+    /// Used to sign several vouchers with the same content.
+    pub nonce: u128,
+}
+
+type BalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+type MintVoucherOf<T> = MintVoucher<
+    <T as frame_system::Config>::Hash,
+    BalanceOf<T>,
+    <T as frame_system::Config>::BlockNumber,
+>;
+
+/// A lock on a batch of NFTs.
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+pub struct TokenLock<AccountId> {
+    /// The owner of the NFTs when they were locked.
+    pub owner: AccountId,
+    pub end_idx: u128,
+}
+
+/// The identifier of FT locks set by token owners.
+pub const OWNER_LOCK_ID: LockIdentifier = *b"nftowner";
+
+/// The number of batches of NFTs migrated to storage version 1 per block.
+pub const MIGRATION_BATCH: u32 = 100;
+
+/// Prefixed to signed vouchers so that a signature can't be used for anything else.
+pub const VOUCHER_CONTEXT: &[u8] = b"starry/mint-voucher";
+
+/// Called before tokens are minted, returning an error rejects the mint.
+///
+/// `range` is `Some((start_idx, end_idx))` for NFTs and `None` for FTs.
+pub trait OnTokenMint<Hash, AccountId> {
+    fn on_mint(collection_id: &Hash, range: Option<(u128, u128)>, to: &AccountId, amount: u128) -> DispatchResult;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl<Hash, AccountId> OnTokenMint<Hash, AccountId> for Tuple {
+    fn on_mint(collection_id: &Hash, range: Option<(u128, u128)>, to: &AccountId, amount: u128) -> DispatchResult {
+        for_tuples!( #( Tuple::on_mint(collection_id, range, to, amount)?; )* );
+        Ok(())
+    }
+}
+
+/// Called before tokens are transferred, returning an error rejects the transfer.
+///
+/// `range` is `Some((start_idx, end_idx))` for NFTs and `None` for FTs.
+pub trait OnTokenTransfer<Hash, AccountId> {
+    fn on_transfer(
+        collection_id: &Hash,
+        range: Option<(u128, u128)>,
+        from: &AccountId,
+        to: &AccountId,
+        amount: u128,
+    ) -> DispatchResult;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl<Hash, AccountId> OnTokenTransfer<Hash, AccountId> for Tuple {
+    fn on_transfer(
+        collection_id: &Hash,
+        range: Option<(u128, u128)>,
+        from: &AccountId,
+        to: &AccountId,
+        amount: u128,
+    ) -> DispatchResult {
+        for_tuples!( #( Tuple::on_transfer(collection_id, range, from, to, amount)?; )* );
+        Ok(())
+    }
+}
+
+/// Called before tokens are burned, returning an error rejects the burn.
+///
+/// `range` is `Some((start_idx, end_idx))` for NFTs and `None` for FTs.
+pub trait OnTokenBurn<Hash, AccountId> {
+    fn on_burn(collection_id: &Hash, range: Option<(u128, u128)>, from: &AccountId, amount: u128) -> DispatchResult;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl<Hash, AccountId> OnTokenBurn<Hash, AccountId> for Tuple {
+    fn on_burn(collection_id: &Hash, range: Option<(u128, u128)>, from: &AccountId, amount: u128) -> DispatchResult {
+        for_tuples!( #( Tuple::on_burn(collection_id, range, from, amount)?; )* );
+        Ok(())
+    }
+}
+
+/// Pallets that refer to a batch of NFTs by its start_idx, such as escrow accounts,
+/// tell pallet-nft not to merge it with adjacent batches.
+pub trait KeepsTokenRanges<Hash, AccountId> {
+    /// Whether the batch of NFTs starting at `start_idx` and owned by `owner` must be kept.
+    fn keeps_range(collection_id: &Hash, start_idx: u128, owner: &AccountId) -> bool;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl<Hash, AccountId> KeepsTokenRanges<Hash, AccountId> for Tuple {
+    fn keeps_range(collection_id: &Hash, start_idx: u128, owner: &AccountId) -> bool {
+        for_tuples!( #(
+            if Tuple::keeps_range(collection_id, start_idx, owner) {
+                return true;
+            }
+        )* );
+        false
+    }
+}
+
 pub trait Config: frame_system::Config {
     /// Because this pallet emits events, it depends on the runtime's definition of an event.
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
     type Collection: CollectionInterface<Self::Hash, Self::AccountId>;
+    /// Called before tokens are minted.
+    type OnMint: OnTokenMint<Self::Hash, Self::AccountId>;
+    /// Called before tokens are transferred.
+    type OnTransfer: OnTokenTransfer<Self::Hash, Self::AccountId>;
+    /// Called before tokens are burned.
+    type OnBurn: OnTokenBurn<Self::Hash, Self::AccountId>;
+    /// The maximum number of transfers in `batch_transfer`.
+    type MaxBatchTransfers: Get<u32>;
+    /// The currency used to pay for NFTs minted with vouchers.
+    type Currency: Currency<Self::AccountId>;
+    /// The signature of vouchers.
+    type Signature: Parameter + Verify<Signer = Self::Signer>;
+    /// The signer of vouchers.
+    type Signer: IdentifyAccount<AccountId = Self::AccountId>;
+    /// Batches of NFTs that must not be merged.
+    type RangeKeepers: KeepsTokenRanges<Self::Hash, Self::AccountId>;
+    /// The origin allowed to freeze tokens and force transfers, e.g. root or a council.
+    type ForceOrigin: EnsureOrigin<Self::Origin>;
+    /// The maximum number of locks set by pallets on NFTs of a collection, and of locks set by
+    /// an owner on its NFTs in a collection. They are checked on every transfer and burn.
+    type MaxTokenLocks: Get<u32>;
 }
 
 decl_storage! {
@@ -70,8 +274,77 @@ decl_storage! {
         /// The set of minted NFTs. (collection_id, start_idx) => nft_info
         pub Tokens get(fn tokens): double_map hasher(blake2_128_concat) T::Hash, hasher(blake2_128_concat) u128 => TokenInfo<T::AccountId>;
 
+        /// The start_idx of minted NFTs by end_idx. (collection_id, end_idx) => start_idx
+        pub RangeStarts get(fn range_start): double_map hasher(blake2_128_concat) T::Hash, hasher(blake2_128_concat) u128 => Option<u128>;
+
         /// The set of Collection burned count. collection_id => burned amount
         pub BurnedTokens get(fn burned_tokens): map hasher(blake2_128_concat) T::Hash => u128;
+
+        /// The set of collections being destroyed. collection_id => ()
+        pub DestroyingCollections get(fn destroying_collections): map hasher(blake2_128_concat) T::Hash => ();
+
+        /// Locks set by pallets on NFTs. collection_id, (lock_id, start_idx) => lock
+        pub NonFungibleLocks get(fn non_fungible_locks): double_map hasher(blake2_128_concat) T::Hash, hasher(blake2_128_concat) (LockIdentifier, u128) => Option<TokenLock<T::AccountId>>;
+
+        /// The number of locks set by pallets on NFTs of a collection. collection_id => count
+        pub LockCount get(fn lock_count): map hasher(blake2_128_concat) T::Hash => u32;
+
+        /// Locks set by owners on their NFTs. (collection_id, owner), start_idx => end_idx
+        pub OwnerLocks get(fn owner_locks): double_map hasher(blake2_128_concat) (T::Hash, T::AccountId), hasher(blake2_128_concat) u128 => Option<u128>;
+
+        /// The number of locks set by an owner on its NFTs. (collection_id, owner) => count
+        pub OwnerLockCount get(fn owner_lock_count): map hasher(blake2_128_concat) (T::Hash, T::AccountId) => u32;
+
+        /// Locks on FTs. (collection_id, address), lock_id => amount
+        pub FungibleLocks get(fn fungible_locks): double_map hasher(blake2_128_concat) (T::Hash, T::AccountId), hasher(blake2_128_concat) LockIdentifier => u128;
+
+        /// Accounts allowed to sign vouchers of a collection. collection_id, address => ()
+        pub Minters get(fn minters): double_map hasher(blake2_128_concat) T::Hash, hasher(blake2_128_concat) T::AccountId => ();
+
+        /// How many NFTs were minted with a voucher. voucher_hash => amount
+        pub VoucherMinted get(fn voucher_minted): map hasher(identity) T::Hash => u128;
+
+        /// Accounts allowed to change the uri of NFTs of a collection. collection_id, address => ()
+        pub MetadataUpdaters get(fn metadata_updaters): double_map hasher(blake2_128_concat) T::Hash, hasher(blake2_128_concat) T::AccountId => ();
+
+        /// Collections whose uri of NFTs can't be changed. collection_id => ()
+        pub FrozenMetadata get(fn frozen_metadata): map hasher(blake2_128_concat) T::Hash => ();
+
+        /// Collections whose tokens can't be transferred or minted. collection_id => ()
+        pub FrozenCollections get(fn frozen_collections): map hasher(blake2_128_concat) T::Hash => ();
+
+        /// Accounts that can't transfer their tokens of a collection. collection_id, address => ()
+        pub FrozenAccounts get(fn frozen_accounts): double_map hasher(blake2_128_concat) T::Hash, hasher(blake2_128_concat) T::AccountId => ();
+
+        /// The version of storage, used by migrations.
+        pub StorageVersion get(fn storage_version): u32;
+
+        /// The raw key of `Tokens` the migration to storage version 1 continues after.
+        pub MigrationCursor get(fn migration_cursor): Option<Vec<u8>>;
+    }
+    add_extra_genesis {
+        /// NFTs minted at genesis, the collections must exist. (collection_id, receiver, uri, amount)
+        config(non_fungible_tokens): Vec<(T::Hash, T::AccountId, Vec<u8>, u128)>;
+        /// FTs minted at genesis, the collections must exist. (collection_id, receiver, amount)
+        config(fungible_tokens): Vec<(T::Hash, T::AccountId, u128)>;
+        build(|config: &GenesisConfig<T>| {
+            for (collection_id, receiver, uri, amount) in &config.non_fungible_tokens {
+                assert!(T::Collection::collection_exist(*collection_id), "Collection of genesis NFTs does not exist");
+                let collection = T::Collection::get_collection(*collection_id);
+                <Module<T> as NFTInterface<_, _>>::_mint_non_fungible(receiver.clone(), *collection_id, *amount, uri.clone(), &collection)
+                    .expect("Genesis NFTs can be minted");
+            }
+
+            for (collection_id, receiver, amount) in &config.fungible_tokens {
+                assert!(T::Collection::collection_exist(*collection_id), "Collection of genesis FTs does not exist");
+                let collection = T::Collection::get_collection(*collection_id);
+                <Module<T> as NFTInterface<_, _>>::_mint_fungible(receiver.clone(), *collection_id, *amount, &collection)
+                    .expect("Genesis FTs can be minted");
+            }
+
+            // New chains don't need the migration to storage version 1.
+            StorageVersion::put(1);
+        });
     }
 }
 
@@ -82,23 +355,85 @@ decl_event!(
         AccountId = <T as frame_system::Config>::AccountId,
         Hash = <T as frame_system::Config>::Hash,
     {
-        /// One or a batch of NFTs were Minted. \[collection_id, start_idx, end_idx\]
-        NonFungibleTokenMinted(Hash, u128, u128),
+        /// One or a batch of NFTs were Minted. \[receiver, collection_id, start_idx, end_idx, amount\]
+        NonFungibleTokenMinted(AccountId, Hash, u128, u128, u128),
+
+        /// Some FTs were minted. \[receiver, collection_id, amount\]
+        FungibleTokenMinted(AccountId, Hash, u128),
+
+        /// One or a batch of NFTs were transfered to another account. \[sender, receiver, collection_id, start_idx, end_idx, amount\]
+        NonFungibleTokenTransferred(AccountId, AccountId, Hash, u128, u128, u128),
+
+        /// Some FTs were transfered to another account. \[sender, receiver, collection_id, amount\]
+        FungibleTokenTransferred(AccountId, AccountId, Hash, u128),
+
+        /// One or a batch of NFTs were burned. \[sender, collection_id, start_idx, end_idx, amount\]
+        NonFungibleTokenBurned(AccountId, Hash, u128, u128, u128),
+
+        /// Some FTs were burned. \[sender, collection_id, amount\]
+        FungibleTokenBurned(AccountId, Hash, u128),
+
+        /// Some token ranges of a collection were removed, the rest will be removed by
+        /// the next call. \[owner, collection_id, removed_ranges\]
+        CollectionPartiallyDestroyed(AccountId, Hash, u32),
+
+        /// A collection and all of its tokens were destroyed. \[owner, collection_id\]
+        CollectionDestroyed(AccountId, Hash),
+
+        /// One or a batch of NFTs were locked by owner. \[owner, collection_id, start_idx, end_idx\]
+        NonFungibleTokenLocked(AccountId, Hash, u128, u128),
 
-        /// Some FTs were minted. \[collection_id\]
-        FungibleTokenMinted(Hash),
+        /// A lock on NFTs was removed by owner. \[owner, collection_id, start_idx\]
+        NonFungibleTokenUnlocked(AccountId, Hash, u128),
 
-        /// One or a batch of NFTs were transfered to another account. \[receiver, collection_id\]
-        NonFungibleTokenTransferred(AccountId, Hash),
+        /// Some FTs were locked by owner. \[owner, collection_id, amount\]
+        FungibleTokenLocked(AccountId, Hash, u128),
 
-        // some FTs were transfered to another account. \[receiver, collection_id\]
-        FungibleTokenTransferred(AccountId, Hash),
+        /// A lock on FTs was removed by owner. \[owner, collection_id\]
+        FungibleTokenUnlocked(AccountId, Hash),
 
-        // One or a batch of NFTs were burned. \[sender, collection_id\]
-        NonFungibleTokenBurned(AccountId, Hash),
+        /// An account was allowed to sign vouchers. \[collection_id, minter\]
+        MinterAdded(Hash, AccountId),
 
-        // Some FTs were burned.  \[sender, collection_id\]
-        FungibleTokenBurned(AccountId, Hash),
+        /// A minter was removed. \[collection_id, minter\]
+        MinterRemoved(Hash, AccountId),
+
+        /// NFTs were minted with a voucher. \[buyer, collection_id, voucher_hash, amount\]
+        VoucherRedeemed(AccountId, Hash, Hash, u128),
+
+        /// A voucher can no longer be used. \[collection_id, voucher_hash\]
+        VoucherCancelled(Hash, Hash),
+
+        /// Adjacent batches of NFTs were merged. \[owner, collection_id, start_idx, end_idx\]
+        RangesMerged(AccountId, Hash, u128, u128),
+
+        /// The uri of a NFT was changed. \[collection_id, token_id, old_uri, new_uri\]
+        TokenUriSet(Hash, u128, Vec<u8>, Vec<u8>),
+
+        /// An account was allowed to change the uri of NFTs. \[collection_id, updater\]
+        MetadataUpdaterAdded(Hash, AccountId),
+
+        /// A metadata updater was removed. \[collection_id, updater\]
+        MetadataUpdaterRemoved(Hash, AccountId),
+
+        /// The uri of NFTs of a collection can no longer be changed. \[collection_id\]
+        MetadataFrozen(Hash),
+
+        /// A collection was frozen by the force origin. \[collection_id\]
+        CollectionFrozen(Hash),
+
+        /// A collection was thawed by the force origin. \[collection_id\]
+        CollectionThawed(Hash),
+
+        /// The tokens of an account in a collection were frozen by the force origin. \[collection_id, who\]
+        AccountFrozen(Hash, AccountId),
+
+        /// The tokens of an account in a collection were thawed by the force origin. \[collection_id, who\]
+        AccountThawed(Hash, AccountId),
+
+        /// Tokens were transferred by the force origin, `start_idx` is 0 for FTs.
+        /// \[collection_id, from, to, start_idx, amount, reason\]
+        ForceTransferred(Hash, AccountId, AccountId, u128, u128, u32),
     }
 );
 
@@ -120,7 +455,39 @@ decl_error! {
         /// The recipient cannot be the sender.
         ReceiverIsSender,
         /// Wrong token type, for example: cann't mint FTs in NFT Collection.
-        WrongTokenType
+        WrongTokenType,
+        /// Tokens are held by other accounts, so the collection cannot be destroyed.
+        TokensHeldByOthers,
+        /// The collection is being destroyed.
+        CollectionDestroying,
+        /// Tokens of the collection can't be transferred.
+        NonTransferable,
+        /// Tokens are locked.
+        TokenLocked,
+        /// Lock does not exist.
+        LockNotFound,
+        /// Too many transfers in a batch.
+        TooManyTransfers,
+        /// The signer is neither the collection owner nor a minter.
+        NotMinter,
+        /// The signature of the voucher is invalid.
+        BadSignature,
+        /// The voucher has expired.
+        VoucherExpired,
+        /// Not enough NFTs left in the voucher.
+        VoucherExhausted,
+        /// No adjacent batch of NFTs can be merged.
+        NothingToMerge,
+        /// The uri of NFTs of the collection can't be changed.
+        MetadataFrozen,
+        /// The batch of NFTs is referenced by another pallet and can't be split.
+        RangeKept,
+        /// The collection or the tokens of the account are frozen.
+        Frozen,
+        /// The collection or the owner already has `MaxTokenLocks` locks on NFTs.
+        TooManyLocks,
+        /// NFTs are minted in order, the index isn't the next one of the collection.
+        UnexpectedTokenId,
     }
 }
 
@@ -129,6 +496,23 @@ decl_module! {
 
         fn deposit_event() = default;
 
+        fn on_runtime_upgrade() -> Weight {
+            if Self::storage_version() == 0 && !MigrationCursor::exists() {
+                MigrationCursor::put(Tokens::<T>::final_prefix().to_vec());
+                T::DbWeight::get().reads_writes(2, 1)
+            } else {
+                0
+            }
+        }
+
+        fn on_initialize(_n: T::BlockNumber) -> Weight {
+            if MigrationCursor::exists() {
+                Self::migrate_to_v1(MIGRATION_BATCH)
+            } else {
+                T::DbWeight::get().reads(1)
+            }
+        }
+
         /// Mint some FTs.
         ///
         /// The dispatch origin of this call must be _Signed_.
@@ -150,8 +534,6 @@ decl_module! {
 
             Self::_mint_fungible(receiver, collection_id, amount, &collection)?;
 
-            Self::deposit_event(RawEvent::FungibleTokenMinted(collection_id));
-
             Ok(())
         }
 
@@ -180,13 +562,7 @@ decl_module! {
             let collection =T::Collection::get_collection(collection_id);
             ensure!(collection.owner == who, Error::<T>::PermissionDenied);
 
-            let (start_idx, end_idx) = Self::_mint_non_fungible(receiver, collection_id, amount, uri, &collection)?;
-
-            Self::deposit_event(RawEvent::NonFungibleTokenMinted(
-                collection_id,
-                start_idx,
-                end_idx,
-            ));
+            Self::_mint_non_fungible(receiver, collection_id, amount, uri, &collection)?;
 
             Ok(())
         }
@@ -204,9 +580,7 @@ decl_module! {
         pub fn transfer_fungible(origin, receiver: T::AccountId, collection_id: T::Hash, amount:u128) -> DispatchResult {
            let who = ensure_signed(origin)?;
 
-           Self::_transfer_fungible(who, receiver.clone(), collection_id, amount)?;
-
-           Self::deposit_event(RawEvent::FungibleTokenTransferred(receiver, collection_id));
+           Self::_transfer_fungible(who, receiver, collection_id, amount)?;
 
            Ok(())
        }
@@ -224,16 +598,84 @@ decl_module! {
         /// token to be transferred is in this collection.
         /// - `start_idx`: The index of the token or a batch of tokens to be transferred.
         /// - `amount`: How many tokens to transfer.
-        #[weight = 10_000]
+        #[weight = 10_000 + T::DbWeight::get().reads(2 * T::MaxTokenLocks::get() as Weight)]
          pub fn transfer_non_fungible(origin, receiver: T::AccountId, collection_id: T::Hash, start_idx: u128, amount: u128) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
-            Self::_transfer_non_fungible(who, receiver.clone(), collection_id, start_idx, amount)?;
+            Self::_transfer_non_fungible(who, receiver, collection_id, start_idx, amount)?;
 
-            Self::deposit_event(RawEvent::NonFungibleTokenTransferred(
-                receiver,
-                collection_id,
-            ));
+            Ok(())
+        }
+
+        /// Merge a batch of NFTs with the following batches of the same owner and uri.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the owner of the NFTs.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The id of the collection whose token type is NFT.
+        /// - `start_idx`: The index of the first batch.
+        /// - `max_merges`: The maximum number of batches to merge into the first one.
+        #[weight = 10_000 + 10_000 * (max_merges as Weight)]
+        pub fn merge_ranges(origin, collection_id: T::Hash, start_idx: u128, max_merges: u32) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                Tokens::<T>::contains_key(collection_id, start_idx),
+                Error::<T>::TokenNotFound
+            );
+            ensure!(
+                Self::tokens(collection_id, start_idx).owner == who,
+                Error::<T>::PermissionDenied
+            );
+
+            let mut merged = 0;
+            while merged < max_merges && Self::merge_next(&collection_id, start_idx) {
+                merged += 1;
+            }
+            ensure!(merged > 0, Error::<T>::NothingToMerge);
+
+            let end_idx = Self::tokens(collection_id, start_idx).end_idx;
+            Self::deposit_event(RawEvent::RangesMerged(who, collection_id, start_idx, end_idx));
+
+            Ok(())
+        }
+
+        /// Transfer NFTs or FTs of several collections to several accounts.
+        ///
+        /// All transfers succeed or none of them is applied.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        ///
+        /// Parameters:
+        /// - `items`: The transfers, `start_idx` is ignored for FTs. The length can't exceed `MaxBatchTransfers`.
+        #[weight = 10_000 + (10_000 + T::DbWeight::get().reads(2 * T::MaxTokenLocks::get() as Weight)) * (items.len() as Weight)]
+        #[transactional]
+        pub fn batch_transfer(origin, items: Vec<BatchTransferItem<T::AccountId, T::Hash>>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(items.len() as u32 <= T::MaxBatchTransfers::get(), Error::<T>::TooManyTransfers);
+
+            for item in items {
+                ensure!(
+                    T::Collection::collection_exist(item.collection_id),
+                    Error::<T>::CollectionNotFound
+                );
+
+                let collection = T::Collection::get_collection(item.collection_id);
+                match collection.token_type {
+                    Some(TokenType::Fungible) => Self::_transfer_fungible(
+                        who.clone(),
+                        item.receiver,
+                        item.collection_id,
+                        item.amount,
+                    )?,
+                    _ => Self::_transfer_non_fungible(
+                        who.clone(),
+                        item.receiver,
+                        item.collection_id,
+                        item.start_idx,
+                        item.amount,
+                    )?,
+                }
+            }
 
             Ok(())
         }
@@ -250,9 +692,7 @@ decl_module! {
         pub fn burn_fungible(origin, collection_id: T::Hash, amount:u128) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
-            Self::_burn_fungible(who.clone(), collection_id, amount)?;
-
-            Self::deposit_event(RawEvent::FungibleTokenBurned(who, collection_id));
+            Self::_burn_fungible(who, collection_id, amount)?;
 
             Ok(())
         }
@@ -266,13 +706,559 @@ decl_module! {
         /// token to be burned is in this collection
         /// - `start_idx`: The index of the token or a batch of tokens to be burned.
         /// - `amount`: How many tokens to burn.
-        #[weight = 10_000]
+        #[weight = 10_000 + T::DbWeight::get().reads_writes(2 * T::MaxTokenLocks::get() as Weight, 2 * T::MaxTokenLocks::get() as Weight)]
         pub fn burn_non_fungible(origin, collection_id: T::Hash, start_idx: u128, amount: u128) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
-            Self::_burn_non_fungible(who.clone(), collection_id, start_idx, amount)?;
+            Self::_burn_non_fungible(who, collection_id, start_idx, amount)?;
+
+            Ok(())
+        }
+
+        /// Destroy a collection and all of its tokens.
+        ///
+        /// The collection can be destroyed when its total_supply is zero or all tokens are
+        /// owned by the collection owner. Token ranges and other entries are removed in
+        /// batches, so if the collection has more of them than `witness` allows, call it
+        /// again to continue. Once started, no tokens of the collection can be minted or
+        /// transferred. The deposit of the collection is returned when everything is removed.
+        /// The burn hooks are called for every range removed and for the FTs of the owner, so
+        /// they can stop a collection from being destroyed.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The collection to destroy.
+        /// - `witness`: The maximum number of token ranges and other entries to remove in this call.
+        #[weight = 10_000 + 10_000 * (witness.ranges as Weight) + T::DbWeight::get().reads_writes(witness.items as Weight, witness.items as Weight)]
+        #[transactional]
+        pub fn destroy_collection(origin, collection_id: T::Hash, witness: DestroyWitness) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                T::Collection::collection_exist(collection_id),
+                Error::<T>::CollectionNotFound
+            );
+
+            let collection = T::Collection::get_collection(collection_id);
+            ensure!(collection.owner == who, Error::<T>::PermissionDenied);
+
+            if !DestroyingCollections::<T>::contains_key(collection_id) {
+                let balance = Self::address_balances((collection_id, &who));
+                ensure!(
+                    collection.total_supply == 0 || balance == collection.total_supply,
+                    Error::<T>::TokensHeldByOthers
+                );
+
+                DestroyingCollections::<T>::insert(collection_id, ());
+            }
+
+            let ranges: Vec<(u128, TokenInfo<T::AccountId>)> = Tokens::<T>::iter_prefix(collection_id)
+                .take(witness.ranges as usize)
+                .collect();
+            let removed = ranges.len() as u32;
+            for (start_idx, token) in ranges {
+                T::OnBurn::on_burn(&collection_id, Some((start_idx, token.end_idx)), &token.owner, token.end_idx - start_idx + 1)?;
+                Tokens::<T>::remove(collection_id, start_idx);
+                RangeStarts::<T>::remove(collection_id, token.end_idx);
+            }
+
+            let mut items = witness.items as usize;
+            items -= NonFungibleLocks::<T>::drain_prefix(collection_id).take(items).count();
+            items -= OwnerLocks::<T>::drain_prefix((collection_id, who.clone())).take(items).count();
+            items -= Minters::<T>::drain_prefix(collection_id).take(items).count();
+            items -= MetadataUpdaters::<T>::drain_prefix(collection_id).take(items).count();
+            items -= FrozenAccounts::<T>::drain_prefix(collection_id).take(items).count();
+
+            // Entries are left only if the budget ran out.
+            if Tokens::<T>::iter_prefix(collection_id).next().is_some()
+                || (items == 0 && Self::has_collection_items(&collection_id, &who))
+            {
+                Self::deposit_event(RawEvent::CollectionPartiallyDestroyed(who, collection_id, removed));
+                return Ok(());
+            }
+
+            let balance = Self::address_balances((collection_id, &who));
+            if collection.token_type == Some(TokenType::Fungible) && balance > 0 {
+                T::OnBurn::on_burn(&collection_id, None, &who, balance)?;
+            }
+
+            Self::destory_collection(&collection_id, &who);
+            T::Collection::destory_collection(&collection_id);
+
+            Self::deposit_event(RawEvent::CollectionDestroyed(who, collection_id));
+
+            Ok(())
+        }
+
+        /// Lock one or a batch of NFTs, locked NFTs can't be transferred.
+        ///
+        /// An owner can have at most `MaxTokenLocks` locks on NFTs of a collection.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The id of the collection whose token type is NFT and the
+        /// token to be locked is in this collection
+        /// - `start_idx`: The index of the token or a batch of tokens to be locked.
+        /// - `amount`: How many tokens to lock.
+        #[weight = 10_000]
+        pub fn lock_non_fungible(origin, collection_id: T::Hash, start_idx: u128, amount: u128) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let end_idx = Self::lock_end_idx(&who, collection_id, start_idx, amount)?;
+            let key = (collection_id, who.clone());
+            if !OwnerLocks::<T>::contains_key(&key, start_idx) {
+                let count = Self::owner_lock_count(&key);
+                ensure!(count < T::MaxTokenLocks::get(), Error::<T>::TooManyLocks);
+                OwnerLockCount::<T>::insert(&key, count + 1);
+            }
+            OwnerLocks::<T>::insert(&key, start_idx, end_idx);
+
+            Self::deposit_event(RawEvent::NonFungibleTokenLocked(who, collection_id, start_idx, end_idx));
+
+            Ok(())
+        }
+
+        /// Remove a lock set by `lock_non_fungible`.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The id of the collection whose token type is NFT.
+        /// - `start_idx`: The start_idx used to lock the tokens.
+        #[weight = 10_000]
+        pub fn unlock_non_fungible(origin, collection_id: T::Hash, start_idx: u128) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let key = (collection_id, who.clone());
+            ensure!(
+                OwnerLocks::<T>::take(&key, start_idx).is_some(),
+                Error::<T>::LockNotFound
+            );
+            Self::decrease_owner_lock_count(&key, 1);
+
+            Self::deposit_event(RawEvent::NonFungibleTokenUnlocked(who, collection_id, start_idx));
+
+            Ok(())
+        }
+
+        /// Lock some FTs, locked FTs can't be transferred.
+        ///
+        /// Only one lock can be set by the owner, calling it again replaces the amount.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The id of the collection whose token type is FT.
+        /// - `amount`: How many tokens to lock.
+        #[weight = 10_000]
+        pub fn lock_fungible(origin, collection_id: T::Hash, amount: u128) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Self::set_fungible_lock(OWNER_LOCK_ID, &who, collection_id, amount)?;
+
+            Self::deposit_event(RawEvent::FungibleTokenLocked(who, collection_id, amount));
+
+            Ok(())
+        }
+
+        /// Remove a lock set by `lock_fungible`.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The id of the collection whose token type is FT.
+        #[weight = 10_000]
+        pub fn unlock_fungible(origin, collection_id: T::Hash) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            ensure!(
+                FungibleLocks::<T>::contains_key((collection_id, &who), OWNER_LOCK_ID),
+                Error::<T>::LockNotFound
+            );
+
+            Self::remove_fungible_lock(OWNER_LOCK_ID, &who, collection_id);
+
+            Self::deposit_event(RawEvent::FungibleTokenUnlocked(who, collection_id));
+
+            Ok(())
+        }
+
+        /// Allow an account to sign vouchers of a collection.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the owner of the collection.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The id of the collection.
+        /// - `minter`: The account allowed to sign vouchers.
+        #[weight = 10_000]
+        pub fn add_minter(origin, collection_id: T::Hash, minter: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_collection_owner(&who, collection_id)?;
+
+            Minters::<T>::insert(collection_id, &minter, ());
+
+            Self::deposit_event(RawEvent::MinterAdded(collection_id, minter));
+
+            Ok(())
+        }
+
+        /// Remove a minter of a collection, vouchers it signed can no longer be used.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the owner of the collection.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The id of the collection.
+        /// - `minter`: The minter to remove.
+        #[weight = 10_000]
+        pub fn remove_minter(origin, collection_id: T::Hash, minter: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_collection_owner(&who, collection_id)?;
+
+            Minters::<T>::remove(collection_id, &minter);
+
+            Self::deposit_event(RawEvent::MinterRemoved(collection_id, minter));
+
+            Ok(())
+        }
+
+        /// Mint NFTs with a voucher signed by the collection owner or a minter.
+        ///
+        /// The sender pays `voucher.price` for each NFT to the collection owner.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        ///
+        /// Parameters:
+        /// - `voucher`: The voucher.
+        /// - `signer`: The account that signed the voucher.
+        /// - `signature`: The signature of `voucher_payload(voucher)`, which binds the voucher to this chain.
+        /// - `amount`: How many NFTs to mint.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn mint_with_voucher(
+            origin,
+            voucher: MintVoucherOf<T>,
+            signer: T::AccountId,
+            signature: T::Signature,
+            amount: u128
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(amount >= 1, Error::<T>::AmountLessThanOne);
+
+            let collection_id = voucher.collection_id;
+            ensure!(
+                T::Collection::collection_exist(collection_id),
+                Error::<T>::CollectionNotFound
+            );
+
+            let collection = T::Collection::get_collection(collection_id);
+            ensure!(
+                signer == collection.owner || Minters::<T>::contains_key(collection_id, &signer),
+                Error::<T>::NotMinter
+            );
+            ensure!(
+                signature.verify(&Self::voucher_payload(&voucher)[..], &signer),
+                Error::<T>::BadSignature
+            );
+            ensure!(
+                frame_system::Module::<T>::block_number() <= voucher.expiry,
+                Error::<T>::VoucherExpired
+            );
+
+            let voucher_hash = T::Hashing::hash_of(&voucher);
+            let minted = Self::voucher_minted(voucher_hash)
+                .checked_add(amount)
+                .ok_or(Error::<T>::NumOverflow)?;
+            ensure!(minted <= voucher.max_amount, Error::<T>::VoucherExhausted);
+
+            let price = voucher
+                .price
+                .checked_mul(&amount.saturated_into())
+                .ok_or(Error::<T>::NumOverflow)?;
+
+            T::Currency::transfer(&who, &collection.owner, price, ExistenceRequirement::KeepAlive)?;
+            Self::_mint_non_fungible(who.clone(), collection_id, amount, voucher.uri, &collection)?;
+            VoucherMinted::<T>::insert(voucher_hash, minted);
+
+            Self::deposit_event(RawEvent::VoucherRedeemed(who, collection_id, voucher_hash, amount));
+
+            Ok(())
+        }
+
+        /// Stop a voucher from being used.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the
+        /// owner of the collection or a minter.
+        ///
+        /// Parameters:
+        /// - `voucher`: The voucher to cancel.
+        #[weight = 10_000]
+        pub fn cancel_voucher(origin, voucher: MintVoucherOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let collection_id = voucher.collection_id;
+            ensure!(
+                T::Collection::collection_exist(collection_id),
+                Error::<T>::CollectionNotFound
+            );
+
+            let collection = T::Collection::get_collection(collection_id);
+            ensure!(
+                who == collection.owner || Minters::<T>::contains_key(collection_id, &who),
+                Error::<T>::PermissionDenied
+            );
+
+            let voucher_hash = T::Hashing::hash_of(&voucher);
+            VoucherMinted::<T>::insert(voucher_hash, voucher.max_amount);
+
+            Self::deposit_event(RawEvent::VoucherCancelled(collection_id, voucher_hash));
+
+            Ok(())
+        }
+
+        /// Change the uri of a NFT.
+        ///
+        /// If the NFT is in a batch, the batch is split so that only this NFT is changed.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the owner
+        /// of the collection or a metadata updater.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The id of the collection whose token type is NFT.
+        /// - `start_idx`: The index of the batch which contains the NFT.
+        /// - `token_id`: The index of the NFT.
+        /// - `uri`: The new uri.
+        #[weight = 10_000]
+        pub fn set_token_uri(origin, collection_id: T::Hash, start_idx: u128, token_id: u128, uri: Vec<u8>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                T::Collection::collection_exist(collection_id),
+                Error::<T>::CollectionNotFound
+            );
+
+            let collection = T::Collection::get_collection(collection_id);
+            ensure!(
+                who == collection.owner || MetadataUpdaters::<T>::contains_key(collection_id, &who),
+                Error::<T>::PermissionDenied
+            );
+            ensure!(
+                !FrozenMetadata::<T>::contains_key(collection_id),
+                Error::<T>::MetadataFrozen
+            );
+            ensure!(
+                Tokens::<T>::contains_key(collection_id, start_idx),
+                Error::<T>::TokenNotFound
+            );
+
+            let token = Self::tokens(collection_id, start_idx);
+            ensure!(
+                start_idx <= token_id && token_id <= token.end_idx,
+                Error::<T>::TokenNotFound
+            );
+
+            let is_split = start_idx != token_id || token.end_idx != token_id;
+            ensure!(
+                !is_split || !T::RangeKeepers::keeps_range(&collection_id, start_idx, &token.owner),
+                Error::<T>::RangeKept
+            );
 
-            Self::deposit_event(RawEvent::NonFungibleTokenBurned(who, collection_id));
+            if token_id > start_idx {
+                Self::insert_range(&collection_id, start_idx, TokenInfo {
+                    end_idx: token_id - 1,
+                    ..token.clone()
+                });
+            }
+            if token_id < token.end_idx {
+                Self::insert_range(&collection_id, token_id + 1, token.clone());
+            }
+            Self::insert_range(&collection_id, token_id, TokenInfo {
+                end_idx: token_id,
+                owner: token.owner,
+                uri: uri.clone(),
+            });
+
+            Self::deposit_event(RawEvent::TokenUriSet(collection_id, token_id, token.uri, uri));
+
+            Ok(())
+        }
+
+        /// Allow an account to change the uri of NFTs of a collection.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the owner of the collection.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The id of the collection.
+        /// - `updater`: The account allowed to change the uri.
+        #[weight = 10_000]
+        pub fn add_metadata_updater(origin, collection_id: T::Hash, updater: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_collection_owner(&who, collection_id)?;
+
+            MetadataUpdaters::<T>::insert(collection_id, &updater, ());
+
+            Self::deposit_event(RawEvent::MetadataUpdaterAdded(collection_id, updater));
+
+            Ok(())
+        }
+
+        /// Remove a metadata updater of a collection.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the owner of the collection.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The id of the collection.
+        /// - `updater`: The updater to remove.
+        #[weight = 10_000]
+        pub fn remove_metadata_updater(origin, collection_id: T::Hash, updater: T::AccountId) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_collection_owner(&who, collection_id)?;
+
+            MetadataUpdaters::<T>::remove(collection_id, &updater);
+
+            Self::deposit_event(RawEvent::MetadataUpdaterRemoved(collection_id, updater));
+
+            Ok(())
+        }
+
+        /// Stop the uri of NFTs of a collection from being changed. It can't be undone.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the owner of the collection.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The id of the collection.
+        #[weight = 10_000]
+        pub fn freeze_metadata(origin, collection_id: T::Hash) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            Self::ensure_collection_owner(&who, collection_id)?;
+
+            FrozenMetadata::<T>::insert(collection_id, ());
+
+            Self::deposit_event(RawEvent::MetadataFrozen(collection_id));
+
+            Ok(())
+        }
+
+        /// Stop all tokens of a collection from being transferred or minted.
+        ///
+        /// The dispatch origin of this call must be `ForceOrigin`.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The id of the collection.
+        #[weight = 10_000]
+        pub fn freeze_collection(origin, collection_id: T::Hash) -> DispatchResult {
+            T::ForceOrigin::ensure_origin(origin)?;
+            ensure!(
+                T::Collection::collection_exist(collection_id),
+                Error::<T>::CollectionNotFound
+            );
+
+            FrozenCollections::<T>::insert(collection_id, ());
+
+            Self::deposit_event(RawEvent::CollectionFrozen(collection_id));
+
+            Ok(())
+        }
+
+        /// Allow the tokens of a frozen collection to be transferred and minted again.
+        ///
+        /// The dispatch origin of this call must be `ForceOrigin`.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The id of the collection.
+        #[weight = 10_000]
+        pub fn thaw_collection(origin, collection_id: T::Hash) -> DispatchResult {
+            T::ForceOrigin::ensure_origin(origin)?;
+
+            FrozenCollections::<T>::remove(collection_id);
+
+            Self::deposit_event(RawEvent::CollectionThawed(collection_id));
+
+            Ok(())
+        }
+
+        /// Stop an account from transferring its tokens of a collection. It can still receive tokens.
+        ///
+        /// The dispatch origin of this call must be `ForceOrigin`.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The id of the collection.
+        /// - `who`: The account to freeze.
+        #[weight = 10_000]
+        pub fn freeze_account(origin, collection_id: T::Hash, who: T::AccountId) -> DispatchResult {
+            T::ForceOrigin::ensure_origin(origin)?;
+            ensure!(
+                T::Collection::collection_exist(collection_id),
+                Error::<T>::CollectionNotFound
+            );
+
+            FrozenAccounts::<T>::insert(collection_id, &who, ());
+
+            Self::deposit_event(RawEvent::AccountFrozen(collection_id, who));
+
+            Ok(())
+        }
+
+        /// Allow a frozen account to transfer its tokens of a collection again.
+        ///
+        /// The dispatch origin of this call must be `ForceOrigin`.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The id of the collection.
+        /// - `who`: The account to thaw.
+        #[weight = 10_000]
+        pub fn thaw_account(origin, collection_id: T::Hash, who: T::AccountId) -> DispatchResult {
+            T::ForceOrigin::ensure_origin(origin)?;
+
+            FrozenAccounts::<T>::remove(collection_id, &who);
+
+            Self::deposit_event(RawEvent::AccountThawed(collection_id, who));
+
+            Ok(())
+        }
+
+        /// Transfer NFTs or FTs of an account to another account.
+        ///
+        /// Freezes and the transferable flag of the collection are ignored. Locked NFTs and FTs still
+        /// can't be moved, because the pallets holding the locks would be left with stale state.
+        ///
+        /// The dispatch origin of this call must be `ForceOrigin`.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The id of the collection.
+        /// - `from`: The owner of the tokens.
+        /// - `to`: The receiver of the tokens.
+        /// - `start_idx`: The index of the NFTs to transfer, ignored for FTs.
+        /// - `amount`: How many tokens to transfer.
+        /// - `reason`: A code recorded in the event, e.g. the id of a court order.
+        #[weight = 10_000]
+        pub fn force_transfer(
+            origin,
+            collection_id: T::Hash,
+            from: T::AccountId,
+            to: T::AccountId,
+            start_idx: u128,
+            amount: u128,
+            reason: u32,
+        ) -> DispatchResult {
+            T::ForceOrigin::ensure_origin(origin)?;
+            ensure!(
+                T::Collection::collection_exist(collection_id),
+                Error::<T>::CollectionNotFound
+            );
+
+            let collection = T::Collection::get_collection(collection_id);
+            let start_idx = match collection.token_type {
+                Some(TokenType::Fungible) => {
+                    Self::do_transfer_fungible(from.clone(), to.clone(), collection_id, amount, true)?;
+                    0
+                }
+                _ => {
+                    Self::do_transfer_non_fungible(from.clone(), to.clone(), collection_id, start_idx, amount, true)?;
+                    start_idx
+                }
+            };
+
+            Self::deposit_event(RawEvent::ForceTransferred(collection_id, from, to, start_idx, amount, reason));
 
             Ok(())
         }
@@ -288,8 +1274,9 @@ pub trait NFTInterface<Hash, AccountId> {
     fn get_balance(collection_id: &Hash, who: &AccountId) -> u128;
     /// Get the count of tokens burned in a collection.
     fn get_burned_amount(collection_id: &Hash) -> u128;
-    /// Destory a collection by collection_id.
-    fn destory_collection(collection_id: &Hash);
+    /// Destory all tokens of a collection by collection_id, `holder` is the last account
+    /// that may have a balance in the collection.
+    fn destory_collection(collection_id: &Hash, holder: &AccountId);
     /// Mint NFTs
     fn _mint_non_fungible(
         receiver: AccountId,
@@ -329,6 +1316,26 @@ pub trait NFTInterface<Hash, AccountId> {
     ) -> DispatchResult;
     /// burn FTs.
     fn _burn_fungible(who: AccountId, collection_id: Hash, amount: u128) -> DispatchResult;
+    /// Lock NFTs owned by `owner` from start_idx with `id`, return the end_idx of the lock.
+    fn set_non_fungible_lock(
+        id: LockIdentifier,
+        owner: &AccountId,
+        collection_id: Hash,
+        start_idx: u128,
+        amount: u128,
+    ) -> Result<u128, DispatchError>;
+    /// Remove a lock on NFTs.
+    fn remove_non_fungible_lock(id: LockIdentifier, collection_id: Hash, start_idx: u128);
+    /// Check whether any NFT of `owner` between start_idx and end_idx is locked.
+    fn is_locked(collection_id: Hash, owner: &AccountId, start_idx: u128, end_idx: u128) -> bool;
+    /// Lock some FTs of `who` with `id`, an existing lock with the same id is replaced.
+    fn set_fungible_lock(id: LockIdentifier, who: &AccountId, collection_id: Hash, amount: u128) -> DispatchResult;
+    /// Remove a lock on FTs.
+    fn remove_fungible_lock(id: LockIdentifier, who: &AccountId, collection_id: Hash);
+    /// Get the amount of FTs of `who` that can't be transferred.
+    fn locked_balance(collection_id: &Hash, who: &AccountId) -> u128;
+    /// Check whether the collection or the tokens of `who` in it are frozen.
+    fn is_frozen(collection_id: &Hash, who: &AccountId) -> bool;
 }
 
 impl<T: Config> NFTInterface<T::Hash, T::AccountId> for Module<T> {
@@ -348,8 +1355,21 @@ impl<T: Config> NFTInterface<T::Hash, T::AccountId> for Module<T> {
         Self::burned_tokens(collection_id)
     }
 
-    fn destory_collection(collection_id: &T::Hash) {
-        Tokens::<T>::remove_prefix(collection_id)
+    fn destory_collection(collection_id: &T::Hash, holder: &T::AccountId) {
+        Tokens::<T>::remove_prefix(collection_id);
+        LastTokenId::<T>::remove(collection_id);
+        BurnedTokens::<T>::remove(collection_id);
+        AddressBalances::<T>::remove((collection_id, holder));
+        DestroyingCollections::<T>::remove(collection_id);
+        // Both are bounded by `MaxTokenLocks`.
+        NonFungibleLocks::<T>::remove_prefix(collection_id);
+        LockCount::<T>::remove(collection_id);
+        OwnerLocks::<T>::remove_prefix((collection_id, holder));
+        OwnerLockCount::<T>::remove((collection_id, holder));
+        FungibleLocks::<T>::remove_prefix((collection_id, holder));
+        RangeStarts::<T>::remove_prefix(collection_id);
+        FrozenMetadata::<T>::remove(collection_id);
+        FrozenCollections::<T>::remove(collection_id);
     }
 
     fn _mint_non_fungible(
@@ -361,6 +1381,14 @@ impl<T: Config> NFTInterface<T::Hash, T::AccountId> for Module<T> {
     ) -> Result<(u128, u128), DispatchError> {
         // Result<Hash, DispatchError>;
         ensure!(amount >= 1, Error::<T>::AmountLessThanOne);
+        ensure!(
+            !DestroyingCollections::<T>::contains_key(collection_id),
+            Error::<T>::CollectionDestroying
+        );
+        ensure!(
+            !FrozenCollections::<T>::contains_key(collection_id),
+            Error::<T>::Frozen
+        );
 
         if let Some(token_type) = collection.token_type {
             ensure!(
@@ -392,20 +1420,23 @@ impl<T: Config> NFTInterface<T::Hash, T::AccountId> for Module<T> {
             .checked_add(amount)
             .ok_or(Error::<T>::NumOverflow)?;
 
+        T::OnMint::on_mint(&collection_id, Some((start_idx, end_idx)), &receiver, amount)?;
+
         // let new_total_supply =
         //     <pallet_collection::Module<T>>::add_total_supply(collection_id, amount)?;
         T::Collection::add_total_supply(collection_id, amount)?;
 
         LastTokenId::<T>::insert(collection_id, end_idx);
         AddressBalances::<T>::insert((collection_id, &receiver), owner_balance);
-        Tokens::<T>::insert(collection_id, start_idx, token);
+        Self::insert_range(&collection_id, start_idx, token);
 
-        // [receiver, collection_id, start_idx, end_idx, new_total_supply]
-        // Self::deposit_event(RawEvent::NonFungibleTokenMinted(
-        //     collection_id,
-        //     start_idx,
-        //     end_idx,
-        // ));
+        Self::deposit_event(RawEvent::NonFungibleTokenMinted(
+            receiver,
+            collection_id,
+            start_idx,
+            end_idx,
+            amount,
+        ));
 
         Ok((start_idx, end_idx))
     }
@@ -417,6 +1448,14 @@ impl<T: Config> NFTInterface<T::Hash, T::AccountId> for Module<T> {
         collection: &CollectionInfo<T::AccountId>,
     ) -> DispatchResult {
         ensure!(amount >= 1, Error::<T>::AmountLessThanOne);
+        ensure!(
+            !DestroyingCollections::<T>::contains_key(collection_id),
+            Error::<T>::CollectionDestroying
+        );
+        ensure!(
+            !FrozenCollections::<T>::contains_key(collection_id),
+            Error::<T>::Frozen
+        );
 
         if let Some(token_type) = collection.token_type {
             ensure!(
@@ -429,6 +1468,8 @@ impl<T: Config> NFTInterface<T::Hash, T::AccountId> for Module<T> {
             .checked_add(amount)
             .ok_or(Error::<T>::NumOverflow)?;
 
+        T::OnMint::on_mint(&collection_id, None, &receiver, amount)?;
+
         T::Collection::add_total_supply(collection_id, amount)?;
 
         // let new_total_supply =
@@ -436,6 +1477,8 @@ impl<T: Config> NFTInterface<T::Hash, T::AccountId> for Module<T> {
 
         AddressBalances::<T>::insert((collection_id, &receiver), owner_balance);
 
+        Self::deposit_event(RawEvent::FungibleTokenMinted(receiver, collection_id, amount));
+
         Ok(())
     }
 
@@ -446,14 +1489,30 @@ impl<T: Config> NFTInterface<T::Hash, T::AccountId> for Module<T> {
         start_idx: u128,
         amount: u128,
     ) -> DispatchResult {
-        ensure!(who != receiver, Error::<T>::ReceiverIsSender);
+        Self::do_transfer_non_fungible(who, receiver, collection_id, start_idx, amount, false)
+    }
+
+    fn _transfer_fungible(
+        who: T::AccountId,
+        receiver: T::AccountId,
+        collection_id: T::Hash,
+        amount: u128,
+    ) -> DispatchResult {
+        Self::do_transfer_fungible(who, receiver, collection_id, amount, false)
+    }
+
+    fn _burn_non_fungible(
+        who: T::AccountId,
+        collection_id: T::Hash,
+        start_idx: u128,
+        amount: u128,
+    ) -> DispatchResult {
         ensure!(amount >= 1, Error::<T>::AmountLessThanOne);
 
         ensure!(
             T::Collection::collection_exist(collection_id),
             Error::<T>::CollectionNotFound
         );
-
         ensure!(
             Tokens::<T>::contains_key(collection_id, start_idx),
             Error::<T>::TokenNotFound
@@ -468,6 +1527,7 @@ impl<T: Config> NFTInterface<T::Hash, T::AccountId> for Module<T> {
         }
 
         let token = Self::tokens(collection_id, start_idx);
+
         ensure!(token.owner == who, Error::<T>::PermissionDenied);
 
         if amount > 1 {
@@ -479,48 +1539,82 @@ impl<T: Config> NFTInterface<T::Hash, T::AccountId> for Module<T> {
 
             ensure!(token_amount >= &amount, Error::<T>::AmountTooLarge);
         }
-
-        let sender_balance = Self::address_balances((collection_id, &who))
+        let balance = Self::address_balances((collection_id, &who))
             .checked_sub(amount)
             .ok_or(Error::<T>::NumOverflow)?;
-        let receiver_balance = Self::address_balances((collection_id, &receiver))
+        let new_start_idx = start_idx
             .checked_add(amount)
             .ok_or(Error::<T>::NumOverflow)?;
 
-        let sender_start_idx = start_idx
+        let burn_amount = Self::burned_tokens(collection_id)
             .checked_add(amount)
             .ok_or(Error::<T>::NumOverflow)?;
-        let receiver_end_idx = sender_start_idx
-            .checked_sub(1)
-            .ok_or(Error::<T>::NumOverflow)?;
+        let is_burn_all = new_start_idx > token.end_idx;
 
-        let receiver_token = TokenInfo {
-            end_idx: receiver_end_idx,
-            owner: receiver.clone(),
-            uri: token.uri.clone(),
-        };
+        T::OnBurn::on_burn(&collection_id, Some((start_idx, new_start_idx - 1)), &who, amount)?;
 
-        let is_transfer_all = receiver_token.end_idx == token.end_idx;
+        T::Collection::sub_total_supply(collection_id, amount)?;
 
-        AddressBalances::<T>::insert((collection_id, who), sender_balance);
-        AddressBalances::<T>::insert((collection_id, receiver), receiver_balance);
-        Tokens::<T>::insert(collection_id, start_idx, receiver_token);
+        Self::set_balance(&collection_id, &who, balance);
+        Tokens::<T>::remove(collection_id, start_idx);
+        BurnedTokens::<T>::insert(collection_id, burn_amount);
+        Self::remove_burned_locks(&collection_id, &who, start_idx, new_start_idx - 1);
 
-        if !is_transfer_all {
-            Tokens::<T>::insert(collection_id, sender_start_idx, token);
+        if is_burn_all {
+            RangeStarts::<T>::remove(collection_id, token.end_idx);
+        } else {
+            Self::insert_range(&collection_id, new_start_idx, token);
         }
 
+        Self::deposit_event(RawEvent::NonFungibleTokenBurned(
+            who,
+            collection_id,
+            start_idx,
+            new_start_idx - 1,
+            amount,
+        ));
+
         Ok(())
     }
 
-    fn _transfer_fungible(
-        who: T::AccountId,
-        receiver: T::AccountId,
+    fn set_non_fungible_lock(
+        id: LockIdentifier,
+        owner: &T::AccountId,
         collection_id: T::Hash,
+        start_idx: u128,
         amount: u128,
-    ) -> DispatchResult {
+    ) -> Result<u128, DispatchError> {
+        let end_idx = Self::lock_end_idx(owner, collection_id, start_idx, amount)?;
+
+        let lock = TokenLock {
+            owner: owner.clone(),
+            end_idx,
+        };
+        if !NonFungibleLocks::<T>::contains_key(collection_id, (id, start_idx)) {
+            let count = Self::lock_count(collection_id);
+            ensure!(count < T::MaxTokenLocks::get(), Error::<T>::TooManyLocks);
+            LockCount::<T>::insert(collection_id, count + 1);
+        }
+        NonFungibleLocks::<T>::insert(collection_id, (id, start_idx), lock);
+
+        Ok(end_idx)
+    }
+
+    fn remove_non_fungible_lock(id: LockIdentifier, collection_id: T::Hash, start_idx: u128) {
+        if NonFungibleLocks::<T>::take(collection_id, (id, start_idx)).is_some() {
+            Self::decrease_lock_count(&collection_id, 1);
+        }
+    }
+
+    fn is_locked(collection_id: T::Hash, owner: &T::AccountId, start_idx: u128, end_idx: u128) -> bool {
+        NonFungibleLocks::<T>::iter_prefix(collection_id)
+            .any(|((_, lock_start_idx), lock)| lock_start_idx <= end_idx && lock.end_idx >= start_idx)
+            || OwnerLocks::<T>::iter_prefix((collection_id, owner))
+                .any(|(lock_start_idx, lock_end_idx)| lock_start_idx <= end_idx && lock_end_idx >= start_idx)
+    }
+
+    fn set_fungible_lock(id: LockIdentifier, who: &T::AccountId, collection_id: T::Hash, amount: u128) -> DispatchResult {
         ensure!(amount >= 1, Error::<T>::AmountLessThanOne);
-        ensure!(who != receiver, Error::<T>::ReceiverIsSender);
         ensure!(
             T::Collection::collection_exist(collection_id),
             Error::<T>::CollectionNotFound
@@ -534,34 +1628,88 @@ impl<T: Config> NFTInterface<T::Hash, T::AccountId> for Module<T> {
             );
         }
 
-        let sender_balance = Self::address_balances((collection_id, &who));
-        ensure!(sender_balance >= amount, Error::<T>::AmountTooLarge);
+        let balance = Self::address_balances((collection_id, who));
+        ensure!(balance >= amount, Error::<T>::AmountTooLarge);
 
-        let sender_balance = sender_balance
-            .checked_sub(amount)
-            .ok_or(Error::<T>::NumOverflow)?;
-        let receiver_balance = Self::address_balances((collection_id, &receiver))
+        FungibleLocks::<T>::insert((collection_id, who), id, amount);
+
+        Ok(())
+    }
+
+    fn remove_fungible_lock(id: LockIdentifier, who: &T::AccountId, collection_id: T::Hash) {
+        FungibleLocks::<T>::remove((collection_id, who), id);
+    }
+
+    fn locked_balance(collection_id: &T::Hash, who: &T::AccountId) -> u128 {
+        FungibleLocks::<T>::iter_prefix_values((collection_id, who))
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn is_frozen(collection_id: &T::Hash, who: &T::AccountId) -> bool {
+        FrozenCollections::<T>::contains_key(collection_id)
+            || FrozenAccounts::<T>::contains_key(collection_id, who)
+    }
+
+    fn _burn_fungible(who: T::AccountId, collection_id: T::Hash, amount: u128) -> DispatchResult {
+        ensure!(amount >= 1, Error::<T>::AmountLessThanOne);
+
+        ensure!(
+            T::Collection::collection_exist(collection_id),
+            Error::<T>::CollectionNotFound
+        );
+
+        let collection = T::Collection::get_collection(collection_id);
+        if let Some(token_type) = collection.token_type {
+            ensure!(
+                token_type == TokenType::Fungible,
+                Error::<T>::WrongTokenType
+            );
+        }
+
+        let balance = Self::address_balances((collection_id, &who));
+        ensure!(balance >= amount, Error::<T>::AmountTooLarge);
+
+        let balance = balance.checked_sub(amount).ok_or(Error::<T>::NumOverflow)?;
+        let burn_amount = Self::burned_tokens(collection_id)
             .checked_add(amount)
             .ok_or(Error::<T>::NumOverflow)?;
 
-        AddressBalances::<T>::insert((collection_id, who), sender_balance);
-        AddressBalances::<T>::insert((collection_id, receiver), receiver_balance);
+        T::OnBurn::on_burn(&collection_id, None, &who, amount)?;
+
+        T::Collection::sub_total_supply(collection_id, amount)?;
+
+        Self::set_balance(&collection_id, &who, balance);
+        BurnedTokens::<T>::insert(collection_id, burn_amount);
+
+        Self::deposit_event(RawEvent::FungibleTokenBurned(who, collection_id, amount));
 
         Ok(())
     }
+}
 
-    fn _burn_non_fungible(
+impl<T: Config> Module<T> {
+    /// Transfer NFTs, `force` skips the freeze and transferable checks.
+    fn do_transfer_non_fungible(
         who: T::AccountId,
+        receiver: T::AccountId,
         collection_id: T::Hash,
         start_idx: u128,
         amount: u128,
+        force: bool,
     ) -> DispatchResult {
+        ensure!(who != receiver, Error::<T>::ReceiverIsSender);
         ensure!(amount >= 1, Error::<T>::AmountLessThanOne);
 
         ensure!(
             T::Collection::collection_exist(collection_id),
             Error::<T>::CollectionNotFound
         );
+        ensure!(
+            !DestroyingCollections::<T>::contains_key(collection_id),
+            Error::<T>::CollectionDestroying
+        );
+
         ensure!(
             Tokens::<T>::contains_key(collection_id, start_idx),
             Error::<T>::TokenNotFound
@@ -575,8 +1723,12 @@ impl<T: Config> NFTInterface<T::Hash, T::AccountId> for Module<T> {
             );
         }
 
-        let token = Self::tokens(collection_id, start_idx);
+        if !force {
+            ensure!(collection.transferable, Error::<T>::NonTransferable);
+            ensure!(!Self::is_frozen(&collection_id, &who), Error::<T>::Frozen);
+        }
 
+        let token = Self::tokens(collection_id, start_idx);
         ensure!(token.owner == who, Error::<T>::PermissionDenied);
 
         if amount > 1 {
@@ -588,38 +1740,76 @@ impl<T: Config> NFTInterface<T::Hash, T::AccountId> for Module<T> {
 
             ensure!(token_amount >= &amount, Error::<T>::AmountTooLarge);
         }
-        let balance = Self::address_balances((collection_id, &who))
+
+        ensure!(
+            !Self::is_locked(collection_id, &who, start_idx, start_idx + amount - 1),
+            Error::<T>::TokenLocked
+        );
+
+        let sender_balance = Self::address_balances((collection_id, &who))
             .checked_sub(amount)
             .ok_or(Error::<T>::NumOverflow)?;
-        let new_start_idx = start_idx
+        let receiver_balance = Self::address_balances((collection_id, &receiver))
             .checked_add(amount)
             .ok_or(Error::<T>::NumOverflow)?;
 
-        let burn_amount = Self::burned_tokens(collection_id)
+        let sender_start_idx = start_idx
             .checked_add(amount)
             .ok_or(Error::<T>::NumOverflow)?;
-        let is_burn_all = new_start_idx == token.end_idx;
+        let receiver_end_idx = sender_start_idx
+            .checked_sub(1)
+            .ok_or(Error::<T>::NumOverflow)?;
 
-        T::Collection::sub_total_supply(collection_id, amount)?;
+        let receiver_token = TokenInfo {
+            end_idx: receiver_end_idx,
+            owner: receiver.clone(),
+            uri: token.uri.clone(),
+        };
 
-        AddressBalances::<T>::insert((collection_id, who), balance);
-        Tokens::<T>::remove(collection_id, start_idx);
-        BurnedTokens::<T>::insert(collection_id, burn_amount);
+        let is_transfer_all = receiver_token.end_idx == token.end_idx;
 
-        if !is_burn_all {
-            Tokens::<T>::insert(collection_id, new_start_idx, token);
+        T::OnTransfer::on_transfer(&collection_id, Some((start_idx, receiver_end_idx)), &who, &receiver, amount)?;
+
+        Self::set_balance(&collection_id, &who, sender_balance);
+        AddressBalances::<T>::insert((collection_id, &receiver), receiver_balance);
+        Self::insert_range(&collection_id, start_idx, receiver_token);
+
+        if !is_transfer_all {
+            Self::insert_range(&collection_id, sender_start_idx, token);
         }
 
+        Self::deposit_event(RawEvent::NonFungibleTokenTransferred(
+            who,
+            receiver,
+            collection_id,
+            start_idx,
+            receiver_end_idx,
+            amount,
+        ));
+
+        Self::coalesce(&collection_id, start_idx);
+
         Ok(())
     }
 
-    fn _burn_fungible(who: T::AccountId, collection_id: T::Hash, amount: u128) -> DispatchResult {
+    /// Transfer FTs, `force` skips the freeze and transferable checks.
+    fn do_transfer_fungible(
+        who: T::AccountId,
+        receiver: T::AccountId,
+        collection_id: T::Hash,
+        amount: u128,
+        force: bool,
+    ) -> DispatchResult {
         ensure!(amount >= 1, Error::<T>::AmountLessThanOne);
-
+        ensure!(who != receiver, Error::<T>::ReceiverIsSender);
         ensure!(
             T::Collection::collection_exist(collection_id),
             Error::<T>::CollectionNotFound
         );
+        ensure!(
+            !DestroyingCollections::<T>::contains_key(collection_id),
+            Error::<T>::CollectionDestroying
+        );
 
         let collection = T::Collection::get_collection(collection_id);
         if let Some(token_type) = collection.token_type {
@@ -629,19 +1819,239 @@ impl<T: Config> NFTInterface<T::Hash, T::AccountId> for Module<T> {
             );
         }
 
-        let balance = Self::address_balances((collection_id, &who));
-        ensure!(balance >= amount, Error::<T>::AmountTooLarge);
+        if !force {
+            ensure!(collection.transferable, Error::<T>::NonTransferable);
+            ensure!(!Self::is_frozen(&collection_id, &who), Error::<T>::Frozen);
+        }
 
-        let balance = balance.checked_sub(amount).ok_or(Error::<T>::NumOverflow)?;
-        let burn_amount = Self::burned_tokens(collection_id)
+        let sender_balance = Self::address_balances((collection_id, &who));
+        ensure!(sender_balance >= amount, Error::<T>::AmountTooLarge);
+        ensure!(
+            sender_balance - amount >= Self::locked_balance(&collection_id, &who),
+            Error::<T>::TokenLocked
+        );
+
+        let sender_balance = sender_balance
+            .checked_sub(amount)
+            .ok_or(Error::<T>::NumOverflow)?;
+        let receiver_balance = Self::address_balances((collection_id, &receiver))
             .checked_add(amount)
             .ok_or(Error::<T>::NumOverflow)?;
 
-        T::Collection::sub_total_supply(collection_id, amount)?;
+        T::OnTransfer::on_transfer(&collection_id, None, &who, &receiver, amount)?;
 
-        AddressBalances::<T>::insert((collection_id, who), balance);
-        BurnedTokens::<T>::insert(collection_id, burn_amount);
+        Self::set_balance(&collection_id, &who, sender_balance);
+        AddressBalances::<T>::insert((collection_id, &receiver), receiver_balance);
+
+        Self::deposit_event(RawEvent::FungibleTokenTransferred(who, receiver, collection_id, amount));
+
+        Ok(())
+    }
+
+    /// Store a batch of NFTs and index it by end_idx.
+    fn insert_range(collection_id: &T::Hash, start_idx: u128, token: TokenInfo<T::AccountId>) {
+        RangeStarts::<T>::insert(collection_id, token.end_idx, start_idx);
+        Tokens::<T>::insert(collection_id, start_idx, token);
+    }
+
+    /// Merge the batch of NFTs starting at `start_idx` with the next batch if they have
+    /// the same owner and uri and none of them is kept by `RangeKeepers`.
+    fn merge_next(collection_id: &T::Hash, start_idx: u128) -> bool {
+        let mut token = Self::tokens(collection_id, start_idx);
+        let next_start_idx = match token.end_idx.checked_add(1) {
+            Some(next_start_idx) => next_start_idx,
+            None => return false,
+        };
+        if !Tokens::<T>::contains_key(collection_id, next_start_idx) {
+            return false;
+        }
+
+        let next = Self::tokens(collection_id, next_start_idx);
+        if next.owner != token.owner
+            || next.uri != token.uri
+            || T::RangeKeepers::keeps_range(collection_id, start_idx, &token.owner)
+            || T::RangeKeepers::keeps_range(collection_id, next_start_idx, &next.owner)
+        {
+            return false;
+        }
+
+        Tokens::<T>::remove(collection_id, next_start_idx);
+        RangeStarts::<T>::remove(collection_id, token.end_idx);
+        token.end_idx = next.end_idx;
+        Self::insert_range(collection_id, start_idx, token);
+
+        true
+    }
+
+    /// Merge the batch of NFTs starting at `start_idx` with the previous and the next batch.
+    fn coalesce(collection_id: &T::Hash, start_idx: u128) {
+        let previous_start_idx = start_idx
+            .checked_sub(1)
+            .and_then(|previous_end_idx| Self::range_start(collection_id, previous_end_idx));
+
+        let (start_idx, mut merged) = match previous_start_idx {
+            Some(previous_start_idx) if Self::merge_next(collection_id, previous_start_idx) => {
+                (previous_start_idx, true)
+            }
+            _ => (start_idx, false),
+        };
+        merged |= Self::merge_next(collection_id, start_idx);
+
+        if merged {
+            let token = Self::tokens(collection_id, start_idx);
+            Self::deposit_event(RawEvent::RangesMerged(token.owner, *collection_id, start_idx, token.end_idx));
+        }
+    }
+
+    /// Index up to `limit` batches of NFTs by end_idx and merge them with adjacent batches
+    /// of the same owner and uri, continuing after `MigrationCursor`. Storage version 1 is
+    /// set when all batches are migrated.
+    pub(crate) fn migrate_to_v1(limit: u32) -> Weight {
+        let prefix = Tokens::<T>::final_prefix();
+        let mut cursor = match Self::migration_cursor() {
+            Some(cursor) => cursor,
+            None => return T::DbWeight::get().reads(1),
+        };
+
+        let mut migrated: Weight = 0;
+        while migrated < limit as Weight {
+            let key = match sp_io::storage::next_key(&cursor) {
+                Some(key) if key.starts_with(&prefix) => key,
+                _ => {
+                    MigrationCursor::kill();
+                    StorageVersion::put(1);
+                    return T::DbWeight::get().reads_writes(10 * migrated + 2, 7 * migrated + 2);
+                }
+            };
+
+            // A batch merged into a previous one is gone, the key still orders the rest.
+            if let Some((collection_id, start_idx)) = Self::decode_token_key(&key[prefix.len()..]) {
+                let token = Self::tokens(collection_id, start_idx);
+                RangeStarts::<T>::insert(collection_id, token.end_idx, start_idx);
+                Self::coalesce(&collection_id, start_idx);
+            }
+
+            cursor = key;
+            migrated += 1;
+        }
+        MigrationCursor::put(cursor);
+
+        T::DbWeight::get().reads_writes(10 * migrated + 1, 7 * migrated + 1)
+    }
+
+    /// Decode the collection_id and start_idx of a `Tokens` key without its prefix.
+    fn decode_token_key(mut key: &[u8]) -> Option<(T::Hash, u128)> {
+        // Both keys are hashed with blake2_128_concat, 16 bytes of hash before the key.
+        key = key.get(16..)?;
+        let collection_id = T::Hash::decode(&mut key).ok()?;
+        key = key.get(16..)?;
+        let start_idx = u128::decode(&mut key).ok()?;
+        Some((collection_id, start_idx))
+    }
+
+    /// The message signed for a voucher, `(VOUCHER_CONTEXT, genesis_hash, voucher)` encoded.
+    pub fn voucher_payload(voucher: &MintVoucherOf<T>) -> Vec<u8> {
+        let genesis_hash = frame_system::Module::<T>::block_hash(T::BlockNumber::zero());
+        (VOUCHER_CONTEXT, genesis_hash, voucher).encode()
+    }
+
+    fn ensure_collection_owner(who: &T::AccountId, collection_id: T::Hash) -> DispatchResult {
+        ensure!(
+            T::Collection::collection_exist(collection_id),
+            Error::<T>::CollectionNotFound
+        );
+
+        let collection = T::Collection::get_collection(collection_id);
+        ensure!(&collection.owner == who, Error::<T>::PermissionDenied);
 
         Ok(())
     }
+
+    /// Check whether entries removed in batches by `destroy_collection`, other than token
+    /// ranges, are left in a collection.
+    fn has_collection_items(collection_id: &T::Hash, holder: &T::AccountId) -> bool {
+        NonFungibleLocks::<T>::iter_prefix(collection_id).next().is_some()
+            || OwnerLocks::<T>::iter_prefix((collection_id, holder)).next().is_some()
+            || Minters::<T>::iter_prefix(collection_id).next().is_some()
+            || MetadataUpdaters::<T>::iter_prefix(collection_id).next().is_some()
+            || FrozenAccounts::<T>::iter_prefix(collection_id).next().is_some()
+    }
+
+    /// Check that `owner` can lock `amount` NFTs from start_idx, return the end_idx of the lock.
+    fn lock_end_idx(owner: &T::AccountId, collection_id: T::Hash, start_idx: u128, amount: u128) -> Result<u128, DispatchError> {
+        ensure!(amount >= 1, Error::<T>::AmountLessThanOne);
+        ensure!(
+            T::Collection::collection_exist(collection_id),
+            Error::<T>::CollectionNotFound
+        );
+        ensure!(
+            Tokens::<T>::contains_key(collection_id, start_idx),
+            Error::<T>::TokenNotFound
+        );
+
+        let token = Self::tokens(collection_id, start_idx);
+        ensure!(&token.owner == owner, Error::<T>::PermissionDenied);
+
+        let end_idx = start_idx
+            .checked_add(amount - 1)
+            .ok_or(Error::<T>::NumOverflow)?;
+        ensure!(end_idx <= token.end_idx, Error::<T>::AmountTooLarge);
+
+        Ok(end_idx)
+    }
+
+    /// Remove the locks on NFTs of `owner` that were all burned.
+    fn remove_burned_locks(collection_id: &T::Hash, owner: &T::AccountId, start_idx: u128, end_idx: u128) {
+        let burned_locks: Vec<(LockIdentifier, u128)> = NonFungibleLocks::<T>::iter_prefix(collection_id)
+            .filter(|((_, lock_start_idx), lock)| *lock_start_idx >= start_idx && lock.end_idx <= end_idx)
+            .map(|(key, _)| key)
+            .collect();
+
+        Self::decrease_lock_count(collection_id, burned_locks.len() as u32);
+        for key in burned_locks {
+            NonFungibleLocks::<T>::remove(collection_id, key);
+        }
+
+        let key = (*collection_id, owner.clone());
+        let burned_owner_locks: Vec<u128> = OwnerLocks::<T>::iter_prefix(&key)
+            .filter(|(lock_start_idx, lock_end_idx)| *lock_start_idx >= start_idx && *lock_end_idx <= end_idx)
+            .map(|(lock_start_idx, _)| lock_start_idx)
+            .collect();
+
+        Self::decrease_owner_lock_count(&key, burned_owner_locks.len() as u32);
+        for lock_start_idx in burned_owner_locks {
+            OwnerLocks::<T>::remove(&key, lock_start_idx);
+        }
+    }
+
+    /// Record that `removed` locks on NFTs of a collection were removed.
+    fn decrease_lock_count(collection_id: &T::Hash, removed: u32) {
+        let count = Self::lock_count(collection_id).saturating_sub(removed);
+        if count == 0 {
+            LockCount::<T>::remove(collection_id);
+        } else {
+            LockCount::<T>::insert(collection_id, count);
+        }
+    }
+
+    /// Record that `removed` locks set by an owner on its NFTs were removed.
+    fn decrease_owner_lock_count(key: &(T::Hash, T::AccountId), removed: u32) {
+        let count = Self::owner_lock_count(key).saturating_sub(removed);
+        if count == 0 {
+            OwnerLockCount::<T>::remove(key);
+        } else {
+            OwnerLockCount::<T>::insert(key, count);
+        }
+    }
+
+    /// Set the balance of an account in a collection, the record and the FT locks of the
+    /// account are removed when it is zero.
+    fn set_balance(collection_id: &T::Hash, who: &T::AccountId, balance: u128) {
+        if balance == 0 {
+            AddressBalances::<T>::remove((collection_id, who));
+            FungibleLocks::<T>::remove_prefix((collection_id, who));
+        } else {
+            AddressBalances::<T>::insert((collection_id, who), balance);
+        }
+    }
 }
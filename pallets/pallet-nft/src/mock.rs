@@ -6,8 +6,11 @@ use sp_runtime::{
 };
 use frame_system as system;
 use frame_support::{
+	dispatch::{DispatchError, DispatchResult},
+	ensure,
 	traits::TestRandomness,
 };
+use crate::OnTokenTransfer;
 use pallet_collection as pallet_collection;
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -20,6 +23,7 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Module, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
 		NFTModule: pallet_nft::{Module, Call, Storage, Event<T>},
 		CollectionModule: pallet_collection::{Module, Call, Storage, Event<T>},
 	}
@@ -48,24 +52,88 @@ impl system::Config for Test {
 	type BlockHashCount = BlockHashCount;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
 	type SS58Prefix = SS58Prefix;
 }
 
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+	pub const CollectionDeposit: u64 = 10;
+	pub const StringLimit: u32 = 50;
+	pub const MaxBatchTransfers: u32 = 10;
+	pub const MaxTokenLocks: u32 = 3;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+/// Transfers to this account are rejected by `RejectTransferTo`.
+pub const BLOCKED_ADDRESS: u64 = 99;
+
+pub struct RejectTransferTo;
+
+impl OnTokenTransfer<H256, u64> for RejectTransferTo {
+	fn on_transfer(_collection_id: &H256, _range: Option<(u128, u128)>, _from: &u64, to: &u64, _amount: u128) -> DispatchResult {
+		ensure!(*to != BLOCKED_ADDRESS, DispatchError::Other("transfer rejected"));
+		Ok(())
+	}
+}
+
 impl pallet_nft::Config for Test {
 	type Event = Event;
 	type Collection = CollectionModule;
+	type OnTransfer = RejectTransferTo;
+	type OnMint = ();
+	type OnBurn = ();
+	type MaxBatchTransfers = MaxBatchTransfers;
+	type Currency = Balances;
+	type Signature = sp_runtime::testing::TestSignature;
+	type Signer = sp_runtime::testing::UintAuthorityId;
+	type RangeKeepers = ();
+	type ForceOrigin = frame_system::EnsureRoot<u64>;
+	type MaxTokenLocks = MaxTokenLocks;
 }
 
 impl pallet_collection::Config for Test {
 	type Event = Event;
 	type RandomnessSource = TestRandomness;
+	type Currency = Balances;
+	type CollectionDeposit = CollectionDeposit;
+	type StringLimit = StringLimit;
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
-}
\ No newline at end of file
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 100), (3, 100)],
+	}.assimilate_storage(&mut t).unwrap();
+	t.into()
+}
+
+// Build genesis storage with collections and tokens created at genesis.
+pub fn new_test_ext_with_genesis(
+	collections: Vec<(u64, Vec<u8>, bool, Vec<u8>)>,
+	non_fungible_tokens: Vec<(H256, u64, Vec<u8>, u128)>,
+	fungible_tokens: Vec<(H256, u64, u128)>,
+) -> sp_io::TestExternalities {
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_collection::GenesisConfig::<Test> {
+		collections,
+	}.assimilate_storage(&mut t).unwrap();
+	pallet_nft::GenesisConfig::<Test> {
+		non_fungible_tokens,
+		fungible_tokens,
+	}.assimilate_storage(&mut t).unwrap();
+	t.into()
+}
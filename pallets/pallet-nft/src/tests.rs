@@ -1,6 +1,9 @@
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+use crate::{mock::*, AddressBalances, BatchTransferItem, BurnedTokens, DestroyWitness, Error, FrozenAccounts, LastTokenId, MetadataUpdaters, MintVoucher, Minters, NFTInterface, RangeStarts, RawEvent, TokenInfo, Tokens, OWNER_LOCK_ID};
+use frame_support::{assert_noop, assert_ok, dispatch::DispatchError, traits::OnRuntimeUpgrade};
 use pallet_collection::CollectionInterface;
+use codec::Encode;
+use sp_core::H256;
+use sp_runtime::testing::TestSignature;
 
 #[test]
 fn mint_non_fungible_success() {
@@ -573,4 +576,812 @@ fn burn_fungible_failed() {
             Error::<Test>::AmountTooLarge
         );
     });
-}
\ No newline at end of file
+}
+#[test]
+fn destroy_collection_success() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let alice = Origin::signed(alice_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 5).unwrap();
+        NFTModule::transfer_non_fungible(alice.clone(), bob_address, collection_id, 0, 2).unwrap();
+        // Burn before [0, 1] comes back, it would be merged into [0, 4] otherwise.
+        NFTModule::burn_non_fungible(alice.clone(), collection_id, 2, 1).unwrap();
+        NFTModule::transfer_non_fungible(Origin::signed(bob_address), alice_address, collection_id, 0, 2).unwrap();
+
+        assert_eq!(Balances::reserved_balance(alice_address), 10);
+        assert_eq!(NFTModule::address_balances((collection_id, bob_address)), 0);
+        NFTModule::lock_non_fungible(alice.clone(), collection_id, 0, 1).unwrap();
+        NFTModule::lock_non_fungible(alice.clone(), collection_id, 3, 1).unwrap();
+        NFTModule::add_minter(alice.clone(), collection_id, bob_address).unwrap();
+        NFTModule::add_metadata_updater(alice.clone(), collection_id, bob_address).unwrap();
+        NFTModule::freeze_account(Origin::root(), collection_id, bob_address).unwrap();
+
+        // ranges: [0, 1] and [3, 4]
+        assert_ok!(NFTModule::destroy_collection(
+            alice.clone(),
+            collection_id,
+            DestroyWitness { ranges: 1, items: 3 }
+        ));
+        assert_eq!(CollectionModule::collection_exist(collection_id), true);
+        assert_eq!(RangeStarts::<Test>::iter_prefix(collection_id).count(), 1);
+        assert_noop!(
+            NFTModule::transfer_non_fungible(alice.clone(), bob_address, collection_id, 3, 1),
+            Error::<Test>::CollectionDestroying
+        );
+
+        assert_ok!(NFTModule::destroy_collection(
+            alice,
+            collection_id,
+            DestroyWitness { ranges: 1, items: 3 }
+        ));
+
+        assert_eq!(CollectionModule::collection_exist(collection_id), false);
+        assert_eq!(NFTModule::token_exist(collection_id, 3), false);
+        assert_eq!(AddressBalances::<Test>::contains_key((collection_id, alice_address)), false);
+        assert_eq!(LastTokenId::<Test>::contains_key(collection_id), false);
+        assert_eq!(BurnedTokens::<Test>::contains_key(collection_id), false);
+        assert_eq!(NFTModule::owner_lock_count((collection_id, alice_address)), 0);
+        assert_eq!(NFTModule::owner_locks((collection_id, alice_address), 3), None);
+        assert_eq!(Minters::<Test>::contains_key(collection_id, bob_address), false);
+        assert_eq!(MetadataUpdaters::<Test>::contains_key(collection_id, bob_address), false);
+        assert_eq!(FrozenAccounts::<Test>::contains_key(collection_id, bob_address), false);
+        assert_eq!(Balances::reserved_balance(alice_address), 0);
+    });
+}
+
+#[test]
+fn destroy_collection_failed() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(bob_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], true).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_fungible(alice.clone(), alice_address, collection_id, 5).unwrap();
+        NFTModule::transfer_fungible(alice.clone(), bob_address, collection_id, 1).unwrap();
+
+        assert_noop!(
+            NFTModule::destroy_collection(bob, collection_id, DestroyWitness { ranges: 1, items: 1 }),
+            Error::<Test>::PermissionDenied
+        );
+        assert_noop!(
+            NFTModule::destroy_collection(alice, collection_id, DestroyWitness { ranges: 1, items: 1 }),
+            Error::<Test>::TokensHeldByOthers
+        );
+    });
+}
+
+#[test]
+fn transfer_non_transferable_failed() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let alice = Origin::signed(alice_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        CollectionModule::set_transferable(alice.clone(), collection_id, false).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 2).unwrap();
+
+        assert_noop!(
+            NFTModule::transfer_non_fungible(alice.clone(), bob_address, collection_id, 0, 1),
+            Error::<Test>::NonTransferable
+        );
+        assert_ok!(NFTModule::burn_non_fungible(alice, collection_id, 0, 1));
+    });
+}
+
+#[test]
+fn lock_non_fungible_success() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let alice = Origin::signed(alice_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 5).unwrap();
+
+        assert_ok!(NFTModule::lock_non_fungible(alice.clone(), collection_id, 0, 2));
+        assert_eq!(<NFTModule as NFTInterface<_, _>>::is_locked(collection_id, &alice_address, 1, 1), true);
+        assert_eq!(<NFTModule as NFTInterface<_, _>>::is_locked(collection_id, &alice_address, 2, 4), false);
+
+        assert_noop!(
+            NFTModule::transfer_non_fungible(alice.clone(), bob_address, collection_id, 0, 3),
+            Error::<Test>::TokenLocked
+        );
+
+        assert_ok!(NFTModule::unlock_non_fungible(alice.clone(), collection_id, 0));
+        assert_ok!(NFTModule::transfer_non_fungible(alice, bob_address, collection_id, 0, 3));
+    });
+}
+
+#[test]
+fn lock_non_fungible_failed() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(bob_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 5).unwrap();
+
+        assert_noop!(
+            NFTModule::lock_non_fungible(bob.clone(), collection_id, 0, 1),
+            Error::<Test>::PermissionDenied
+        );
+        assert_noop!(
+            NFTModule::lock_non_fungible(alice.clone(), collection_id, 0, 6),
+            Error::<Test>::AmountTooLarge
+        );
+        assert_noop!(
+            NFTModule::unlock_non_fungible(alice, collection_id, 0),
+            Error::<Test>::LockNotFound
+        );
+    });
+}
+
+#[test]
+fn lock_non_fungible_bounded() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(bob_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        for uri in 0..5 {
+            NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![uri], 1).unwrap();
+        }
+        NFTModule::transfer_non_fungible(alice.clone(), bob_address, collection_id, 4, 1).unwrap();
+
+        assert_ok!(NFTModule::lock_non_fungible(alice.clone(), collection_id, 0, 1));
+        assert_ok!(NFTModule::lock_non_fungible(alice.clone(), collection_id, 1, 1));
+        assert_ok!(NFTModule::lock_non_fungible(alice.clone(), collection_id, 2, 1));
+        // Replacing a lock doesn't count.
+        assert_ok!(NFTModule::lock_non_fungible(alice.clone(), collection_id, 2, 1));
+        assert_eq!(NFTModule::owner_lock_count((collection_id, alice_address)), 3);
+        assert_noop!(
+            NFTModule::lock_non_fungible(alice.clone(), collection_id, 3, 1),
+            Error::<Test>::TooManyLocks
+        );
+
+        // Locks of other owners and of pallets have their own budgets.
+        assert_ok!(NFTModule::lock_non_fungible(bob, collection_id, 4, 1));
+        assert_ok!(<NFTModule as NFTInterface<_, _>>::set_non_fungible_lock(
+            *b"testlock",
+            &alice_address,
+            collection_id,
+            3,
+            1
+        ));
+        assert_eq!(NFTModule::lock_count(collection_id), 1);
+
+        assert_ok!(NFTModule::unlock_non_fungible(alice.clone(), collection_id, 0));
+        assert_ok!(NFTModule::burn_non_fungible(alice.clone(), collection_id, 1, 1));
+        assert_eq!(NFTModule::owner_lock_count((collection_id, alice_address)), 1);
+        assert_ok!(NFTModule::burn_non_fungible(alice.clone(), collection_id, 3, 1));
+        assert_eq!(NFTModule::lock_count(collection_id), 0);
+        assert_ok!(NFTModule::lock_non_fungible(alice, collection_id, 0, 1));
+        assert_eq!(NFTModule::owner_lock_count((collection_id, alice_address)), 2);
+    });
+}
+
+#[test]
+fn lock_fungible_success() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let alice = Origin::signed(alice_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], true).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_fungible(alice.clone(), alice_address, collection_id, 10).unwrap();
+
+        assert_ok!(NFTModule::lock_fungible(alice.clone(), collection_id, 6));
+        assert_eq!(<NFTModule as NFTInterface<_, _>>::locked_balance(&collection_id, &alice_address), 6);
+
+        assert_ok!(NFTModule::transfer_fungible(alice.clone(), bob_address, collection_id, 4));
+        assert_noop!(
+            NFTModule::transfer_fungible(alice.clone(), bob_address, collection_id, 1),
+            Error::<Test>::TokenLocked
+        );
+        assert_ok!(NFTModule::burn_fungible(alice.clone(), collection_id, 1));
+
+        assert_ok!(NFTModule::unlock_fungible(alice.clone(), collection_id));
+        assert_ok!(NFTModule::transfer_fungible(alice, bob_address, collection_id, 5));
+    });
+}
+
+#[test]
+fn lock_fungible_failed() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], true).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_fungible(alice.clone(), alice_address, collection_id, 10).unwrap();
+
+        assert_noop!(
+            NFTModule::lock_fungible(alice.clone(), collection_id, 11),
+            Error::<Test>::AmountTooLarge
+        );
+        assert_noop!(
+            NFTModule::unlock_fungible(alice, collection_id),
+            Error::<Test>::LockNotFound
+        );
+    });
+}
+
+#[test]
+fn transfer_rejected_by_hook() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 2).unwrap();
+
+        assert_noop!(
+            NFTModule::transfer_non_fungible(alice.clone(), BLOCKED_ADDRESS, collection_id, 0, 1),
+            DispatchError::Other("transfer rejected")
+        );
+        assert_ok!(NFTModule::transfer_non_fungible(alice, 2, collection_id, 0, 1));
+    });
+}
+
+#[test]
+fn token_events_are_complete() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let alice_address = 1;
+        let bob_address = 2;
+        let alice = Origin::signed(alice_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 5).unwrap();
+        NFTModule::transfer_non_fungible(alice.clone(), bob_address, collection_id, 0, 2).unwrap();
+        NFTModule::burn_non_fungible(alice, collection_id, 2, 3).unwrap();
+
+        let events: Vec<Event> = System::events().into_iter().map(|record| record.event).collect();
+        assert!(events.contains(&Event::pallet_nft(RawEvent::NonFungibleTokenMinted(alice_address, collection_id, 0, 4, 5))));
+        assert!(events.contains(&Event::pallet_nft(RawEvent::NonFungibleTokenTransferred(alice_address, bob_address, collection_id, 0, 1, 2))));
+        assert!(events.contains(&Event::pallet_nft(RawEvent::NonFungibleTokenBurned(alice_address, collection_id, 2, 4, 3))));
+    });
+}
+
+#[test]
+fn batch_transfer_success() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let charlie_address = 3;
+        let alice = Origin::signed(alice_address);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let nft_collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, nft_collection_id, vec![2, 3, 3], 5).unwrap();
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], true).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let ft_collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_fungible(alice.clone(), alice_address, ft_collection_id, 10).unwrap();
+
+        assert_ok!(NFTModule::batch_transfer(alice, vec![
+            BatchTransferItem { receiver: bob_address, collection_id: nft_collection_id, start_idx: 0, amount: 2 },
+            BatchTransferItem { receiver: charlie_address, collection_id: nft_collection_id, start_idx: 2, amount: 1 },
+            BatchTransferItem { receiver: bob_address, collection_id: ft_collection_id, start_idx: 0, amount: 4 },
+        ]));
+
+        assert_eq!(NFTModule::address_balances((nft_collection_id, bob_address)), 2);
+        assert_eq!(NFTModule::address_balances((nft_collection_id, charlie_address)), 1);
+        assert_eq!(NFTModule::address_balances((nft_collection_id, alice_address)), 2);
+        assert_eq!(NFTModule::address_balances((ft_collection_id, bob_address)), 4);
+        assert_eq!(NFTModule::address_balances((ft_collection_id, alice_address)), 6);
+    });
+}
+
+#[test]
+fn batch_transfer_failed() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let alice = Origin::signed(alice_address);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], true).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_fungible(alice.clone(), alice_address, collection_id, 10).unwrap();
+
+        let item = BatchTransferItem { receiver: bob_address, collection_id, start_idx: 0, amount: 1 };
+        assert_noop!(
+            NFTModule::batch_transfer(alice.clone(), vec![item.clone(); 11]),
+            Error::<Test>::TooManyTransfers
+        );
+        assert_noop!(
+            NFTModule::batch_transfer(alice, vec![
+                item,
+                BatchTransferItem { receiver: bob_address, collection_id, start_idx: 0, amount: 10 },
+            ]),
+            Error::<Test>::AmountTooLarge
+        );
+    });
+}
+
+#[test]
+fn mint_with_voucher_success() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let charlie_address = 3;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(bob_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        assert_ok!(NFTModule::add_minter(alice, collection_id, charlie_address));
+
+        let voucher = MintVoucher { collection_id, uri: vec![2, 3, 3], max_amount: 3, price: 5, expiry: 10, nonce: 0 };
+        let signature = TestSignature(charlie_address, NFTModule::voucher_payload(&voucher));
+
+        assert_ok!(NFTModule::mint_with_voucher(bob.clone(), voucher.clone(), charlie_address, signature.clone(), 2));
+        assert_eq!(NFTModule::address_balances((collection_id, bob_address)), 2);
+        assert_eq!(Balances::free_balance(bob_address), 90);
+        assert_eq!(Balances::free_balance(alice_address), 100);
+
+        assert_ok!(NFTModule::mint_with_voucher(bob.clone(), voucher.clone(), charlie_address, signature.clone(), 1));
+        assert_noop!(
+            NFTModule::mint_with_voucher(bob, voucher, charlie_address, signature, 1),
+            Error::<Test>::VoucherExhausted
+        );
+    });
+}
+
+#[test]
+fn mint_with_voucher_failed() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let charlie_address = 3;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(bob_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        let voucher = MintVoucher { collection_id, uri: vec![2, 3, 3], max_amount: 3, price: 5, expiry: 10, nonce: 0 };
+
+        assert_noop!(
+            NFTModule::mint_with_voucher(bob.clone(), voucher.clone(), charlie_address, TestSignature(charlie_address, NFTModule::voucher_payload(&voucher)), 1),
+            Error::<Test>::NotMinter
+        );
+        assert_noop!(
+            NFTModule::mint_with_voucher(bob.clone(), voucher.clone(), alice_address, TestSignature(alice_address, vec![1]), 1),
+            Error::<Test>::BadSignature
+        );
+        // Signatures over the bare voucher or for another chain are rejected.
+        assert_noop!(
+            NFTModule::mint_with_voucher(bob.clone(), voucher.clone(), alice_address, TestSignature(alice_address, voucher.encode()), 1),
+            Error::<Test>::BadSignature
+        );
+        let other_chain = TestSignature(alice_address, NFTModule::voucher_payload(&voucher));
+        frame_system::BlockHash::<Test>::insert(0, sp_core::H256::repeat_byte(9));
+        assert_noop!(
+            NFTModule::mint_with_voucher(bob.clone(), voucher.clone(), alice_address, other_chain, 1),
+            Error::<Test>::BadSignature
+        );
+
+        let signature = TestSignature(alice_address, NFTModule::voucher_payload(&voucher));
+        System::set_block_number(11);
+        assert_noop!(
+            NFTModule::mint_with_voucher(bob.clone(), voucher.clone(), alice_address, signature.clone(), 1),
+            Error::<Test>::VoucherExpired
+        );
+
+        System::set_block_number(1);
+        assert_ok!(NFTModule::cancel_voucher(alice, voucher.clone()));
+        assert_noop!(
+            NFTModule::mint_with_voucher(bob, voucher, alice_address, signature, 1),
+            Error::<Test>::VoucherExhausted
+        );
+    });
+}
+
+#[test]
+fn transfer_non_fungible_merges_ranges() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(bob_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 5).unwrap();
+
+        assert_ok!(NFTModule::transfer_non_fungible(alice.clone(), bob_address, collection_id, 0, 2));
+        assert_ok!(NFTModule::transfer_non_fungible(alice, bob_address, collection_id, 2, 1));
+        assert_eq!(NFTModule::tokens(collection_id, 0).end_idx, 2);
+        assert_eq!(NFTModule::token_exist(collection_id, 2), false);
+
+        assert_ok!(NFTModule::transfer_non_fungible(bob, alice_address, collection_id, 0, 3));
+        let token = NFTModule::tokens(collection_id, 0);
+        assert_eq!(token.owner, alice_address);
+        assert_eq!(token.end_idx, 4);
+        assert_eq!(NFTModule::token_exist(collection_id, 3), false);
+        assert_eq!(NFTModule::range_start(collection_id, 4), Some(0));
+    });
+}
+
+#[test]
+fn merge_ranges_success() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 2).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 2).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![1], 2).unwrap();
+
+        assert_ok!(NFTModule::merge_ranges(alice.clone(), collection_id, 0, 5));
+        assert_eq!(NFTModule::tokens(collection_id, 0).end_idx, 3);
+        assert_eq!(NFTModule::token_exist(collection_id, 2), false);
+        assert_eq!(NFTModule::token_exist(collection_id, 4), true);
+
+        assert_noop!(
+            NFTModule::merge_ranges(alice, collection_id, 0, 5),
+            Error::<Test>::NothingToMerge
+        );
+    });
+}
+
+#[test]
+fn burn_non_fungible_whole_batch() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 2).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![1], 3).unwrap();
+
+        // Burning all but the last NFT keeps the last one.
+        assert_ok!(NFTModule::burn_non_fungible(alice.clone(), collection_id, 2, 2));
+        assert_eq!(NFTModule::tokens(collection_id, 4).end_idx, 4);
+        assert_eq!(NFTModule::range_start(collection_id, 4), Some(4));
+
+        // Burning the whole batch leaves nothing behind.
+        assert_ok!(NFTModule::burn_non_fungible(alice, collection_id, 0, 2));
+        assert_eq!(NFTModule::token_exist(collection_id, 0), false);
+        assert_eq!(NFTModule::token_exist(collection_id, 2), false);
+        assert_eq!(NFTModule::range_start(collection_id, 1), None);
+        assert_eq!(NFTModule::address_balances((collection_id, alice_address)), 1);
+    });
+}
+
+#[test]
+fn fungible_locks_removed_with_balance() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(bob_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], true).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_fungible(alice.clone(), bob_address, collection_id, 10).unwrap();
+
+        assert_ok!(NFTModule::lock_fungible(bob.clone(), collection_id, 6));
+        assert_ok!(NFTModule::burn_fungible(bob, collection_id, 10));
+        assert_eq!(NFTModule::fungible_locks((collection_id, bob_address), OWNER_LOCK_ID), 0);
+    });
+}
+
+#[test]
+fn fungibles_adapter() {
+    use crate::tokens::fungibles::{Inspect, Mutate, Transfer};
+
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let alice = Origin::signed(alice_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], true).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        assert_ok!(<NFTModule as Mutate<_>>::mint_into(collection_id, &alice_address, 10));
+        assert_eq!(<NFTModule as Inspect<_>>::total_issuance(collection_id), 10);
+        assert_ok!(NFTModule::lock_fungible(alice, collection_id, 4));
+        assert_eq!(<NFTModule as Inspect<_>>::reducible_balance(collection_id, &alice_address, false), 6);
+
+        assert_eq!(<NFTModule as Transfer<_>>::transfer(collection_id, &alice_address, &bob_address, 6, false), Ok(6));
+        assert_noop!(
+            <NFTModule as Transfer<_>>::transfer(collection_id, &alice_address, &bob_address, 1, false),
+            Error::<Test>::TokenLocked
+        );
+        assert_eq!(<NFTModule as Mutate<_>>::burn_from(collection_id, &bob_address, 2), Ok(2));
+        assert_eq!(<NFTModule as Inspect<_>>::balance(collection_id, &bob_address), 4);
+        assert_eq!(<NFTModule as Inspect<_>>::total_issuance(collection_id), 8);
+    });
+}
+
+#[test]
+fn nonfungibles_adapter() {
+    use crate::tokens::nonfungibles::{Inspect, Mutate, Transfer};
+
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let alice = Origin::signed(alice_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 2).unwrap();
+
+        assert_eq!(<NFTModule as Inspect<_>>::class_owner(&collection_id), Some(alice_address));
+        assert_noop!(
+            <NFTModule as Mutate<_>>::mint_into(&collection_id, &3, &bob_address),
+            Error::<Test>::UnexpectedTokenId
+        );
+        assert_ok!(<NFTModule as Mutate<_>>::mint_into(&collection_id, &2, &bob_address));
+        assert_eq!(<NFTModule as Inspect<_>>::owner(&collection_id, &2), Some(bob_address));
+
+        // 1 is inside the batch [0, 1] until 0 is moved out of it.
+        assert_eq!(<NFTModule as Inspect<_>>::owner(&collection_id, &1), None);
+        assert_ok!(NFTModule::lock_non_fungible(alice, collection_id, 0, 1));
+        assert_eq!(<NFTModule as Inspect<_>>::can_transfer(&collection_id, &0), false);
+        assert_ok!(NFTModule::unlock_non_fungible(Origin::signed(alice_address), collection_id, 0));
+        assert_ok!(<NFTModule as Transfer<_>>::transfer(&collection_id, &0, &3));
+        assert_eq!(<NFTModule as Inspect<_>>::owner(&collection_id, &0), Some(3));
+        assert_eq!(<NFTModule as Inspect<_>>::owner(&collection_id, &1), Some(alice_address));
+
+        assert_ok!(<NFTModule as Mutate<_>>::burn_from(&collection_id, &1));
+        assert_eq!(<NFTModule as Inspect<_>>::owner(&collection_id, &1), None);
+    });
+}
+
+#[test]
+fn migrate_to_v1_merges_ranges() {
+    new_test_ext().execute_with(|| {
+        let collection_id = H256::repeat_byte(1);
+        let token = |end_idx, owner| TokenInfo { end_idx, owner, uri: vec![2, 3, 3] };
+        // Batches written before they were indexed by end_idx.
+        Tokens::<Test>::insert(collection_id, 0, token(1, 1));
+        Tokens::<Test>::insert(collection_id, 2, token(2, 1));
+        Tokens::<Test>::insert(collection_id, 3, token(4, 1));
+        Tokens::<Test>::insert(collection_id, 5, token(5, 2));
+        assert_eq!(NFTModule::storage_version(), 0);
+
+        NFTModule::on_runtime_upgrade();
+        let mut blocks = 0;
+        while NFTModule::storage_version() == 0 {
+            NFTModule::migrate_to_v1(1);
+            blocks += 1;
+        }
+
+        assert!(blocks <= 5);
+        assert_eq!(NFTModule::migration_cursor(), None);
+        assert_eq!(NFTModule::tokens(collection_id, 0).end_idx, 4);
+        assert_eq!(Tokens::<Test>::contains_key(collection_id, 2), false);
+        assert_eq!(Tokens::<Test>::contains_key(collection_id, 3), false);
+        assert_eq!(NFTModule::tokens(collection_id, 5).end_idx, 5);
+        assert_eq!(NFTModule::range_start(collection_id, 4), Some(0));
+        assert_eq!(NFTModule::range_start(collection_id, 5), Some(5));
+        assert_eq!(RangeStarts::<Test>::iter_prefix(collection_id).count(), 2);
+    });
+}
+
+#[test]
+fn set_token_uri_success() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(bob_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 5).unwrap();
+
+        assert_ok!(NFTModule::set_token_uri(alice.clone(), collection_id, 0, 2, vec![1]));
+        assert_eq!(NFTModule::tokens(collection_id, 0).end_idx, 1);
+        assert_eq!(NFTModule::tokens(collection_id, 2).uri, vec![1]);
+        assert_eq!(NFTModule::tokens(collection_id, 2).end_idx, 2);
+        assert_eq!(NFTModule::tokens(collection_id, 3).uri, vec![2, 3, 3]);
+        assert_eq!(NFTModule::tokens(collection_id, 3).end_idx, 4);
+
+        assert_ok!(NFTModule::add_metadata_updater(alice, collection_id, bob_address));
+        assert_ok!(NFTModule::set_token_uri(bob, collection_id, 2, 2, vec![2]));
+        assert_eq!(NFTModule::tokens(collection_id, 2).uri, vec![2]);
+    });
+}
+
+#[test]
+fn set_token_uri_failed() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(bob_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 5).unwrap();
+
+        assert_noop!(
+            NFTModule::set_token_uri(bob, collection_id, 0, 2, vec![1]),
+            Error::<Test>::PermissionDenied
+        );
+        assert_noop!(
+            NFTModule::set_token_uri(alice.clone(), collection_id, 0, 5, vec![1]),
+            Error::<Test>::TokenNotFound
+        );
+
+        assert_ok!(NFTModule::freeze_metadata(alice.clone(), collection_id));
+        assert_noop!(
+            NFTModule::set_token_uri(alice, collection_id, 0, 2, vec![1]),
+            Error::<Test>::MetadataFrozen
+        );
+    });
+}
+
+#[test]
+fn freeze_success() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(bob_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 5).unwrap();
+
+        assert_ok!(NFTModule::freeze_account(Origin::root(), collection_id, alice_address));
+        assert!(<NFTModule as NFTInterface<_, _>>::is_frozen(&collection_id, &alice_address));
+        assert_noop!(
+            NFTModule::transfer_non_fungible(alice.clone(), bob_address, collection_id, 0, 1),
+            Error::<Test>::Frozen
+        );
+
+        assert_ok!(NFTModule::thaw_account(Origin::root(), collection_id, alice_address));
+        assert_ok!(NFTModule::transfer_non_fungible(alice.clone(), bob_address, collection_id, 0, 1));
+
+        assert_ok!(NFTModule::freeze_collection(Origin::root(), collection_id));
+        assert!(<NFTModule as NFTInterface<_, _>>::is_frozen(&collection_id, &bob_address));
+        assert_noop!(
+            NFTModule::transfer_non_fungible(bob, alice_address, collection_id, 0, 1),
+            Error::<Test>::Frozen
+        );
+        assert_noop!(
+            NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 1),
+            Error::<Test>::Frozen
+        );
+
+        assert_ok!(NFTModule::thaw_collection(Origin::root(), collection_id));
+        assert_ok!(NFTModule::transfer_non_fungible(alice, bob_address, collection_id, 1, 1));
+    });
+}
+
+#[test]
+fn freeze_failed() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+
+        assert_noop!(
+            NFTModule::freeze_collection(alice.clone(), collection_id),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            NFTModule::freeze_account(alice.clone(), collection_id, alice_address),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            NFTModule::force_transfer(alice, collection_id, alice_address, 2, 0, 1, 0),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            NFTModule::freeze_collection(Origin::root(), Default::default()),
+            Error::<Test>::CollectionNotFound
+        );
+    });
+}
+
+#[test]
+fn force_transfer_success() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let alice_address = 1;
+        let bob_address = 2;
+        let alice = Origin::signed(alice_address);
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 5).unwrap();
+        NFTModule::transfer_non_fungible(alice.clone(), bob_address, collection_id, 0, 2).unwrap();
+
+        assert_ok!(NFTModule::freeze_account(Origin::root(), collection_id, bob_address));
+        assert_ok!(NFTModule::force_transfer(Origin::root(), collection_id, bob_address, alice_address, 0, 2, 7));
+
+        assert_eq!(NFTModule::tokens(collection_id, 0).owner, alice_address);
+        assert_eq!(NFTModule::address_balances((collection_id, bob_address)), 0);
+        assert_eq!(NFTModule::address_balances((collection_id, alice_address)), 5);
+        assert!(System::events().into_iter().map(|record| record.event).any(|event| event
+            == Event::pallet_nft(RawEvent::ForceTransferred(collection_id, bob_address, alice_address, 0, 2, 7))));
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], true).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let ft_collection_id = <CollectionModule as CollectionInterface<_, _>>::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_fungible(alice.clone(), alice_address, ft_collection_id, 10).unwrap();
+        assert_ok!(NFTModule::lock_fungible(alice, ft_collection_id, 6));
+
+        assert_noop!(
+            NFTModule::force_transfer(Origin::root(), ft_collection_id, alice_address, bob_address, 0, 5, 7),
+            Error::<Test>::TokenLocked
+        );
+        assert_ok!(NFTModule::force_transfer(Origin::root(), ft_collection_id, alice_address, bob_address, 0, 4, 7));
+        assert_eq!(NFTModule::address_balances((ft_collection_id, bob_address)), 4);
+    });
+}
+
+#[test]
+fn genesis_tokens_success() {
+    let nft_collection_id = CollectionModule::next_collection_id(&1, Some(vec![1]));
+    let ft_collection_id = CollectionModule::next_collection_id(&1, Some(vec![2]));
+
+    new_test_ext_with_genesis(
+        vec![(1, vec![2, 3, 3], false, vec![1]), (1, vec![2, 3, 3], true, vec![2])],
+        vec![(nft_collection_id, 2, vec![2, 3, 3], 5)],
+        vec![(ft_collection_id, 2, 100)],
+    ).execute_with(|| {
+        assert_eq!(NFTModule::tokens(nft_collection_id, 0).end_idx, 4);
+        assert_eq!(NFTModule::tokens(nft_collection_id, 0).owner, 2);
+        assert_eq!(NFTModule::address_balances((nft_collection_id, 2)), 5);
+        assert_eq!(NFTModule::address_balances((ft_collection_id, 2)), 100);
+        assert_eq!(CollectionModule::collections(ft_collection_id).total_supply, 100);
+        assert_eq!(NFTModule::storage_version(), 1);
+    });
+}
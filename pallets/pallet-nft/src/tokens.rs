@@ -0,0 +1,215 @@
+//! Adapters for the FRAME token traits.
+//!
+//! `frame-support` 3.0.0 doesn't provide `traits::tokens::fungibles` and
+//! `traits::tokens::nonfungibles` yet, so the traits are defined here with the signatures of
+//! later `frame-support` versions. After upgrading Substrate, the definitions can be removed
+//! and the impls pointed at `frame_support::traits::tokens` instead.
+//!
+//! Methods that need types missing from `frame-support` 3.0.0, such as `can_deposit` and
+//! `can_withdraw` of `fungibles::Inspect`, are left out until then.
+
+use crate::{Config, DestroyingCollections, Error, LastTokenId, Module, NFTInterface, Tokens};
+use frame_support::{
+    dispatch::{DispatchError, DispatchResult},
+    ensure,
+};
+use pallet_collection::CollectionInterface;
+use sp_std::vec::Vec;
+
+/// Traits for a set of fungible assets, from `frame_support::traits::tokens::fungibles`.
+pub mod fungibles {
+    use super::*;
+
+    /// Trait for providing balance-inspection access to a set of named fungible assets.
+    pub trait Inspect<AccountId> {
+        /// Means of identifying one asset class from another.
+        type AssetId;
+        /// Scalar type for representing balance of an account.
+        type Balance;
+        /// The total amount of issuance in the system.
+        fn total_issuance(asset: Self::AssetId) -> Self::Balance;
+        /// The minimum balance any single account may have.
+        fn minimum_balance(asset: Self::AssetId) -> Self::Balance;
+        /// Get the `asset` balance of `who`.
+        fn balance(asset: Self::AssetId, who: &AccountId) -> Self::Balance;
+        /// Get the maximum amount of `asset` that `who` can withdraw/transfer successfully.
+        fn reducible_balance(asset: Self::AssetId, who: &AccountId, keep_alive: bool) -> Self::Balance;
+    }
+
+    /// Trait for providing a set of named fungible assets which can be created and destroyed.
+    pub trait Mutate<AccountId>: Inspect<AccountId> {
+        /// Attempt to increase the `asset` balance of `who` by `amount`.
+        fn mint_into(asset: Self::AssetId, who: &AccountId, amount: Self::Balance) -> DispatchResult;
+        /// Attempt to reduce the `asset` balance of `who` by `amount`, return the amount burned.
+        fn burn_from(asset: Self::AssetId, who: &AccountId, amount: Self::Balance) -> Result<Self::Balance, DispatchError>;
+    }
+
+    /// Trait for providing a set of named fungible assets which can only be transferred.
+    pub trait Transfer<AccountId>: Inspect<AccountId> {
+        /// Transfer funds from one account into another, return the amount transferred.
+        fn transfer(
+            asset: Self::AssetId,
+            source: &AccountId,
+            dest: &AccountId,
+            amount: Self::Balance,
+            keep_alive: bool,
+        ) -> Result<Self::Balance, DispatchError>;
+    }
+}
+
+/// Traits for a set of non-fungible assets, from `frame_support::traits::tokens::nonfungibles`.
+pub mod nonfungibles {
+    use super::*;
+
+    /// Trait for providing an interface to many read-only NFT-like sets of asset instances.
+    pub trait Inspect<AccountId> {
+        /// Type for identifying an asset instance.
+        type InstanceId;
+        /// Type for identifying an asset class (an identifier for an independent collection of
+        /// asset instances).
+        type ClassId;
+        /// Returns the owner of asset `instance` of `class`, or `None` if the asset doesn't exist.
+        fn owner(class: &Self::ClassId, instance: &Self::InstanceId) -> Option<AccountId>;
+        /// Returns the owner of the asset `class`, if there is one.
+        fn class_owner(_class: &Self::ClassId) -> Option<AccountId> {
+            None
+        }
+        /// Returns the attribute value of `instance` of `class` corresponding to `key`.
+        fn attribute(_class: &Self::ClassId, _instance: &Self::InstanceId, _key: &[u8]) -> Option<Vec<u8>> {
+            None
+        }
+        /// Returns `true` if the asset `instance` of `class` may be transferred.
+        fn can_transfer(_class: &Self::ClassId, _instance: &Self::InstanceId) -> bool {
+            true
+        }
+    }
+
+    /// Trait for providing an interface for multiple classes of NFT-like assets which may be
+    /// minted, burned and/or have attributes set on them.
+    pub trait Mutate<AccountId>: Inspect<AccountId> {
+        /// Mint some asset `instance` of `class` to be owned by `who`.
+        fn mint_into(class: &Self::ClassId, instance: &Self::InstanceId, who: &AccountId) -> DispatchResult;
+        /// Burn some asset `instance` of `class`.
+        fn burn_from(class: &Self::ClassId, instance: &Self::InstanceId) -> DispatchResult;
+    }
+
+    /// Trait for providing a non-fungible sets of assets which can only be transferred.
+    pub trait Transfer<AccountId>: Inspect<AccountId> {
+        /// Transfer asset `instance` of `class` into `destination` account.
+        fn transfer(class: &Self::ClassId, instance: &Self::InstanceId, destination: &AccountId) -> DispatchResult;
+    }
+}
+
+impl<T: Config> fungibles::Inspect<T::AccountId> for Module<T> {
+    type AssetId = T::Hash;
+    type Balance = u128;
+
+    fn total_issuance(asset: T::Hash) -> u128 {
+        T::Collection::get_collection(asset).total_supply
+    }
+
+    fn minimum_balance(_asset: T::Hash) -> u128 {
+        0
+    }
+
+    fn balance(asset: T::Hash, who: &T::AccountId) -> u128 {
+        Self::get_balance(&asset, who)
+    }
+
+    fn reducible_balance(asset: T::Hash, who: &T::AccountId, _keep_alive: bool) -> u128 {
+        if Self::is_frozen(&asset, who) {
+            return 0;
+        }
+        Self::get_balance(&asset, who).saturating_sub(Self::locked_balance(&asset, who))
+    }
+}
+
+impl<T: Config> fungibles::Mutate<T::AccountId> for Module<T> {
+    fn mint_into(asset: T::Hash, who: &T::AccountId, amount: u128) -> DispatchResult {
+        let collection = T::Collection::get_collection(asset);
+        Self::_mint_fungible(who.clone(), asset, amount, &collection)
+    }
+
+    fn burn_from(asset: T::Hash, who: &T::AccountId, amount: u128) -> Result<u128, DispatchError> {
+        Self::_burn_fungible(who.clone(), asset, amount)?;
+        Ok(amount)
+    }
+}
+
+impl<T: Config> fungibles::Transfer<T::AccountId> for Module<T> {
+    fn transfer(
+        asset: T::Hash,
+        source: &T::AccountId,
+        dest: &T::AccountId,
+        amount: u128,
+        _keep_alive: bool,
+    ) -> Result<u128, DispatchError> {
+        Self::_transfer_fungible(source.clone(), dest.clone(), asset, amount)?;
+        Ok(amount)
+    }
+}
+
+/// An instance is the NFT at the start of a batch, NFTs inside a batch can be addressed after
+/// the NFTs before them are moved out of it.
+impl<T: Config> nonfungibles::Inspect<T::AccountId> for Module<T> {
+    type InstanceId = u128;
+    type ClassId = T::Hash;
+
+    fn owner(class: &T::Hash, instance: &u128) -> Option<T::AccountId> {
+        if Tokens::<T>::contains_key(class, instance) {
+            Some(Self::tokens(class, instance).owner)
+        } else {
+            None
+        }
+    }
+
+    fn class_owner(class: &T::Hash) -> Option<T::AccountId> {
+        if T::Collection::collection_exist(*class) {
+            Some(T::Collection::get_collection(*class).owner)
+        } else {
+            None
+        }
+    }
+
+    fn can_transfer(class: &T::Hash, instance: &u128) -> bool {
+        match <Self as nonfungibles::Inspect<_>>::owner(class, instance) {
+            Some(owner) => {
+                T::Collection::get_collection(*class).transferable
+                    && !DestroyingCollections::<T>::contains_key(class)
+                    && !Self::is_frozen(class, &owner)
+                    && !Self::is_locked(*class, &owner, *instance, *instance)
+            }
+            None => false,
+        }
+    }
+}
+
+impl<T: Config> nonfungibles::Mutate<T::AccountId> for Module<T> {
+    /// NFTs are minted in order, so `instance` must be the next index of the collection.
+    fn mint_into(class: &T::Hash, instance: &u128, who: &T::AccountId) -> DispatchResult {
+        let next_idx = if LastTokenId::<T>::contains_key(class) {
+            Self::last_token_id(class).checked_add(1).ok_or(Error::<T>::NumOverflow)?
+        } else {
+            0
+        };
+        ensure!(*instance == next_idx, Error::<T>::UnexpectedTokenId);
+
+        let collection = T::Collection::get_collection(*class);
+        Self::_mint_non_fungible(who.clone(), *class, 1, Vec::new(), &collection)?;
+        Ok(())
+    }
+
+    fn burn_from(class: &T::Hash, instance: &u128) -> DispatchResult {
+        let owner = <Self as nonfungibles::Inspect<_>>::owner(class, instance)
+            .ok_or(Error::<T>::TokenNotFound)?;
+        Self::_burn_non_fungible(owner, *class, *instance, 1)
+    }
+}
+
+impl<T: Config> nonfungibles::Transfer<T::AccountId> for Module<T> {
+    fn transfer(class: &T::Hash, instance: &u128, destination: &T::AccountId) -> DispatchResult {
+        let owner = <Self as nonfungibles::Inspect<_>>::owner(class, instance)
+            .ok_or(Error::<T>::TokenNotFound)?;
+        Self::_transfer_non_fungible(owner, destination.clone(), *class, *instance, 1)
+    }
+}
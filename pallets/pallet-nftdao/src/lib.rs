@@ -32,19 +32,26 @@
 //! * `process_proposal` - After the grace period, the proposal needs to be processed.
 //! * `ragequit` - Burn shares and exchange for corresponding assets..
 //!
+//! DAOs can be created in the chain spec with the `daos` genesis config.
+//!
+//! A tribute NFT is escrowed with the NFTs and FTs linked to it in pallet-graph. While the proposal
+//! is open, `TributeSubtrees` keeps the `subtree_hash` of the tribute when it was submitted. A
+//! proposal whose tribute subtree changed since then doesn't pass and the tribute goes back.
+//!
 //! [`Call`]: ./enum.Call.html
 //! [`Config`]: ./trait.Config.html
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::{Decode, Encode};
-use frame_support::traits::{Currency, ExistenceRequirement::AllowDeath};
+use frame_support::traits::{Currency, ExistenceRequirement::AllowDeath, Get};
 
 use frame_support::{
     decl_error, decl_event, decl_module, decl_storage,
     dispatch::{DispatchError, DispatchResult},
     ensure,
     traits::Randomness,
+    weights::Weight,
     Parameter,
 };
 use sp_runtime::traits::{CheckedDiv, CheckedMul, CheckedSub, SaturatedConversion, Zero};
@@ -60,7 +67,8 @@ use sp_std::{cmp::max, vec::Vec};
 
 use sp_core::TypeId;
 
-use pallet_nft::NFTInterface;
+use pallet_graph::GraphInterface;
+use pallet_nft::{KeepsTokenRanges, NFTInterface};
 
 #[cfg(test)]
 mod mock;
@@ -136,6 +144,7 @@ pub trait Config: frame_system::Config {
 
     type Currency: Currency<Self::AccountId>;
     type NFT: NFTInterface<Self::Hash, Self::AccountId>;
+    type Graph: GraphInterface<Self::Hash, Self::AccountId>;
 }
 
 decl_storage! {
@@ -148,6 +157,8 @@ decl_storage! {
         pub Members get(fn member): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) T::AccountId => Member;
         /// A set of dao's escrow account. dao account => dao escrows account
         pub Escrows get(fn escrow): map hasher(blake2_128_concat)  T::AccountId => T::AccountId;
+        /// The dao of each escrow account. dao escrows account => dao account
+        pub EscrowDAOs get(fn escrow_dao): map hasher(blake2_128_concat) T::AccountId => Option<T::AccountId>;
         /// The last proposalId in each DAO. dao account => proposal id
         pub LastProposalId get(fn last_proposal_id): map hasher(blake2_128_concat) T::AccountId  => Option<u128>;
         /// A set of proposals. (dao account, proposal id) => proposal
@@ -158,7 +169,34 @@ decl_storage! {
         pub ProposalQueues get(fn proposal_queue): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) u128 => u128;
         /// A set of members who voted on the proposal (dao account, proposal queue index), member account => ()
         pub VoteMembers get(fn vote_member): double_map hasher(blake2_128_concat) (T::AccountId, u128), hasher(blake2_128_concat) T::AccountId => ();
+        /// The subtree hash of a tribute NFT with NFTs or FTs linked to it when the proposal was submitted. (dao account, proposal id) => subtree hash
+        pub TributeSubtrees get(fn tribute_subtree): double_map hasher(blake2_128_concat) T::AccountId, hasher(blake2_128_concat) u128 => Option<T::Hash>;
+        /// The version of storage, used by migrations.
+        pub StorageVersion get(fn storage_version): u32;
+
+    }
+    add_extra_genesis {
+        /// DAOs created at genesis, with the parameters of `create_dao`.
+        /// (summoner, metadata, period_duration, voting_period, grace_period, shares_requested, proposal_deposit, processing_reward, dilution_bound)
+        config(daos): Vec<(T::AccountId, Vec<u8>, u128, u128, u128, u128, BalanceOf<T>, BalanceOf<T>, u128)>;
+        build(|config: &GenesisConfig<T>| {
+            for (summoner, metadata, period_duration, voting_period, grace_period, shares_requested, proposal_deposit, processing_reward, dilution_bound) in &config.daos {
+                Module::<T>::do_create_dao(
+                    summoner.clone(),
+                    metadata.clone(),
+                    *period_duration,
+                    *voting_period,
+                    *grace_period,
+                    *shares_requested,
+                    *proposal_deposit,
+                    *processing_reward,
+                    *dilution_bound,
+                ).expect("Genesis DAOs can be created");
+            }
 
+            // New chains don't need the migration to storage version 1.
+            StorageVersion::put(1);
+        });
     }
 }
 
@@ -242,6 +280,14 @@ decl_module! {
         // Events must be initialized if they are used by the pallet.
         fn deposit_event() = default;
 
+        fn on_runtime_upgrade() -> Weight {
+            if Self::storage_version() == 0 {
+                Self::migrate_to_v1()
+            } else {
+                0
+            }
+        }
+
         /// Create a new DAO.
         ///
         /// The dispatch origin of this call must be _Signed_.
@@ -260,40 +306,17 @@ decl_module! {
         pub fn create_dao(origin, metadata: Vec<u8>, period_duration: u128, voting_period: u128, grace_period: u128, shares_requested: u128, proposal_deposit: BalanceOf<T>, processing_reward: BalanceOf<T>, dilution_bound: u128 ) -> DispatchResult {
             let who = ensure_signed(origin)?;
 
-            ensure!(proposal_deposit >= processing_reward, Error::<T>::DepositSmallerThanReward);
-            ensure!(period_duration > Zero::zero(), Error::<T>::PeriodDurationShouldLargeThanZero);
-            ensure!(voting_period > Zero::zero(), Error::<T>::VotingDurationShouldLargeThanZero);
-            ensure!(grace_period > Zero::zero(), Error::<T>::GracePeriodShouldLargeThanZero);
-            ensure!(dilution_bound > Zero::zero(), Error::<T>::DilutionBoundShouldLargeThanZero);
-
-            let dao_id = Self::dao_id(&who, &metadata)?;
-            let dao_account = Self::dao_account_id(&dao_id);
-            let escrow_id = Self::dao_escrow_id(&dao_id);
-
-            let block_number = <system::Pallet<T>>::block_number();
-
-            let dao = DAOInfo {
-                account_id: dao_account.clone(),
-                escrow_id: escrow_id.clone(),
+            let (dao_account, escrow_id) = Self::do_create_dao(
+                who.clone(),
+                metadata,
                 period_duration,
                 voting_period,
                 grace_period,
-                metadata,
-                total_shares: shares_requested,
-                summoning_time: block_number,
-                dilution_bound,
+                shares_requested,
                 proposal_deposit,
-                processing_reward
-            };
-
-            let member = Member {
-                shares: shares_requested,
-                highest_index_yes_vote: 0
-            };
-
-            DAOs::<T>::insert(&dao_account, dao);
-            Escrows::<T>::insert(&dao_account, &escrow_id);
-            Members::<T>::insert(&dao_account, &who, member);
+                processing_reward,
+                dilution_bound,
+            )?;
 
             Self::deposit_event(RawEvent::DAOCreated(who, dao_account, escrow_id));
 
@@ -346,6 +369,11 @@ decl_module! {
                 max_total_shares_at_yes_vote: 0
             };
 
+            let tribute_subtree = match tribute_nft {
+                Some((collection_id, token_id)) => T::Graph::subtree_hash(&collection_id, token_id)?,
+                None => None,
+            };
+
             if let Some((collection_id, token_id)) = tribute_nft {
                 T::NFT::_transfer_non_fungible(who.clone(), escrow_id.clone(), collection_id, token_id, 1)?;
                 // UserNFT::<T>::insert((&dao_account, &escrow_id), (collection_id, token_id), ());
@@ -359,6 +387,9 @@ decl_module! {
 
             LastProposalId::<T>::insert(&dao_account, &proposal_id);
             Proposals::<T>::insert(&dao_account, &proposal_id, proposal);
+            if let Some(subtree_hash) = tribute_subtree {
+                TributeSubtrees::<T>::insert(&dao_account, &proposal_id, subtree_hash);
+            }
 
             // emit event
             Self::deposit_event(RawEvent::ProposalSubmitted(proposal_id));
@@ -393,6 +424,7 @@ decl_module! {
                 let escrow_id = Self::escrow(&dao_account);
 
                 Proposals::<T>::insert(&dao_account, &proposal_id, &proposal);
+                TributeSubtrees::<T>::remove(&dao_account, &proposal_id);
 
                 if let Some((collection_id, token_id)) = proposal.tribute_nft {
                     T::NFT::_transfer_non_fungible(escrow_id.clone(), who.clone(), collection_id, token_id, 1)?;
@@ -604,9 +636,18 @@ decl_module! {
 
                 Proposals::<T>::insert(&dao_account, &proposal_id, &proposal);
 
+                // Members voted on the tribute with the subtree it had when submitted.
+                let tribute_subtree = TributeSubtrees::<T>::take(&dao_account, &proposal_id);
+                let tribute_unchanged = match proposal.tribute_nft {
+                    Some((collection_id, token_id)) => T::Graph::subtree_hash(&collection_id, token_id).ok() == Some(tribute_subtree),
+                    None => true,
+                };
+
                 let dilution_bound = &dao.dilution_bound;
                 let dilution = &dao.total_shares.checked_mul(*dilution_bound).ok_or(Error::<T>::NumOverflow)?;
-                let did_pass = if proposal.yes_votes > proposal.no_votes {
+                let did_pass = if !tribute_unchanged {
+                    false
+                } else if proposal.yes_votes > proposal.no_votes {
                     dilution > &proposal.max_total_shares_at_yes_vote
                 } else {
                     false
@@ -757,12 +798,71 @@ decl_module! {
     }
 }
 
+impl<T: Config> KeepsTokenRanges<T::Hash, T::AccountId> for Module<T> {
+    /// NFTs of proposals are referenced by their start_idx.
+    fn keeps_range(_collection_id: &T::Hash, _start_idx: u128, owner: &T::AccountId) -> bool {
+        DAOs::<T>::contains_key(owner) || EscrowDAOs::<T>::contains_key(owner)
+    }
+}
+
 impl<T: Config> Module<T> {
     /// Account of this pallet.
     pub fn account_id() -> T::AccountId {
         PALLET_ID.into_account()
     }
 
+    /// Create a DAO summoned by `who`, return the dao account and its escrow account.
+    #[allow(clippy::too_many_arguments)]
+    fn do_create_dao(
+        who: T::AccountId,
+        metadata: Vec<u8>,
+        period_duration: u128,
+        voting_period: u128,
+        grace_period: u128,
+        shares_requested: u128,
+        proposal_deposit: BalanceOf<T>,
+        processing_reward: BalanceOf<T>,
+        dilution_bound: u128,
+    ) -> Result<(T::AccountId, T::AccountId), DispatchError> {
+        ensure!(proposal_deposit >= processing_reward, Error::<T>::DepositSmallerThanReward);
+        ensure!(period_duration > Zero::zero(), Error::<T>::PeriodDurationShouldLargeThanZero);
+        ensure!(voting_period > Zero::zero(), Error::<T>::VotingDurationShouldLargeThanZero);
+        ensure!(grace_period > Zero::zero(), Error::<T>::GracePeriodShouldLargeThanZero);
+        ensure!(dilution_bound > Zero::zero(), Error::<T>::DilutionBoundShouldLargeThanZero);
+
+        let dao_id = Self::dao_id(&who, &metadata)?;
+        let dao_account = Self::dao_account_id(&dao_id);
+        let escrow_id = Self::dao_escrow_id(&dao_id);
+
+        let block_number = <system::Pallet<T>>::block_number();
+
+        let dao = DAOInfo {
+            account_id: dao_account.clone(),
+            escrow_id: escrow_id.clone(),
+            period_duration,
+            voting_period,
+            grace_period,
+            metadata,
+            total_shares: shares_requested,
+            summoning_time: block_number,
+            dilution_bound,
+            proposal_deposit,
+            processing_reward
+        };
+
+        let member = Member {
+            shares: shares_requested,
+            highest_index_yes_vote: 0
+        };
+
+        DAOs::<T>::insert(&dao_account, dao);
+        Escrows::<T>::insert(&dao_account, &escrow_id);
+        EscrowDAOs::<T>::insert(&escrow_id, &dao_account);
+        Members::<T>::insert(&dao_account, &who, member);
+
+        Ok((dao_account, escrow_id))
+    }
+
     /// nonce plus one
     fn nonce_increment() -> Result<u128, DispatchError> {
         let nonce = Nonce::try_mutate(|nonce| -> Result<u128, DispatchError> {
@@ -803,6 +903,19 @@ impl<T: Config> Module<T> {
         escrow_id.into_account()
     }
 
+    /// Index the escrow accounts of existing DAOs, so that their tribute NFTs are not merged.
+    pub(crate) fn migrate_to_v1() -> Weight {
+        let mut count: Weight = 0;
+        for (dao_account, escrow_id) in Escrows::<T>::iter() {
+            EscrowDAOs::<T>::insert(escrow_id, dao_account);
+            count = count.saturating_add(1);
+        }
+
+        StorageVersion::put(1);
+
+        T::DbWeight::get().reads_writes(count, count.saturating_add(1))
+    }
+
     /// id plus one
     pub fn proposal_id_increment(dao_account: &T::AccountId) -> Result<u128, DispatchError> {
         if let Some(proposal_id) = Self::last_proposal_id(dao_account) {
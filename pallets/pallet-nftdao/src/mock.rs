@@ -23,6 +23,7 @@ frame_support::construct_runtime!(
         CollectionModule: pallet_collection::{Module, Call, Storage, Event<T>},
         NFTModule: pallet_nft::{Module, Call, Storage, Event<T>},
         DaoModule: pallet_dao::{Module, Call, Storage, Event<T>},
+        GraphModule: pallet_graph::{Module, Call, Storage, Event<T>},
         Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
     }
 );
@@ -59,6 +60,13 @@ impl system::Config for Test {
 
 parameter_types! {
     pub const ExistentialDeposit: u64 = 1;
+    pub const CollectionDeposit: u64 = 0;
+    pub const StringLimit: u32 = 50;
+    pub const MaxBatchTransfers: u32 = 10;
+    pub const MaxTokenLocks: u32 = 3;
+    pub const MaxEquippables: u32 = 2;
+    pub const MaxDepth: u32 = 6;
+    pub const MaxDescendants: u32 = 5;
 }
 
 impl pallet_balances::Config for Test {
@@ -74,11 +82,24 @@ impl pallet_balances::Config for Test {
 impl pallet_collection::Config for Test {
     type Event = Event;
     type RandomnessSource = TestRandomness;
+    type Currency = Balances;
+    type CollectionDeposit = CollectionDeposit;
+    type StringLimit = StringLimit;
 }
 
 impl pallet_nft::Config for Test {
     type Event = Event;
     type Collection = CollectionModule;
+    type OnTransfer = ();
+    type OnMint = ();
+    type OnBurn = ();
+    type MaxBatchTransfers = MaxBatchTransfers;
+    type Currency = Balances;
+    type Signature = sp_runtime::testing::TestSignature;
+    type Signer = sp_runtime::testing::UintAuthorityId;
+    type RangeKeepers = (GraphModule, DaoModule);
+    type ForceOrigin = frame_system::EnsureRoot<u64>;
+    type MaxTokenLocks = MaxTokenLocks;
 }
 
 impl pallet_dao::Config for Test {
@@ -87,6 +108,17 @@ impl pallet_dao::Config for Test {
     type RandomnessSource = TestRandomness;
     type Currency = Balances;
     type NFT = NFTModule;
+    type Graph = GraphModule;
+}
+
+impl pallet_graph::Config for Test {
+    type Event = Event;
+    type Collection = CollectionModule;
+    type NFT = NFTModule;
+    type StringLimit = StringLimit;
+    type MaxEquippables = MaxEquippables;
+    type MaxDepth = MaxDepth;
+    type MaxDescendants = MaxDescendants;
 }
 
 pub const PERIOD_DURATION: u128 = 2;
@@ -2,6 +2,7 @@ use crate::{mock::*, Error};
 use codec::Encode;
 use frame_support::traits::Currency;
 use frame_support::{assert_noop, assert_ok};
+use pallet_graph::GraphInterface;
 use sp_core::H256;
 
 #[test]
@@ -261,6 +262,94 @@ fn cancel_proposal_and_back_tribute() {
     });
 }
 
+#[test]
+fn submit_proposal_and_tribute_nft_with_subtree() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+
+        let new_dao_account =
+            create_a_dao(&alice_address, PROPOSAL_DEPOSIT, PROPOSAL_DEPOSIT);
+        let escrow_id = DaoModule::escrow(&new_dao_account);
+
+        let token = mint_a_nft(&alice_address);
+        let child = mint_a_nft(&alice_address);
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), child.0, child.1, token.0, token.1));
+        let subtree_hash = <GraphModule as GraphInterface<_, _>>::subtree_hash(&token.0, token.1).unwrap();
+        assert!(subtree_hash.is_some());
+
+        assert_ok!(DaoModule::submit_proposal(
+            alice.clone(),
+            new_dao_account.clone(),
+            alice_address,
+            1,
+            0,
+            Some(token.clone()),
+            Vec::new(),
+            Some(Vec::new())
+        ));
+
+        assert_eq!(DaoModule::tribute_subtree(&new_dao_account, 0), subtree_hash);
+        assert_eq!(NFTModule::tokens(token.0, token.1).owner, escrow_id);
+        assert_eq!(<GraphModule as GraphInterface<_, _>>::descendants(&token.0, token.1), vec![child]);
+
+        assert_ok!(DaoModule::cancel_proposal(
+            alice.clone(),
+            new_dao_account.clone(),
+            0
+        ));
+
+        assert_eq!(DaoModule::tribute_subtree(&new_dao_account, 0), None);
+        assert_eq!(NFTModule::tokens(token.0, token.1).owner, alice_address);
+        assert_eq!(<GraphModule as GraphInterface<_, _>>::descendants(&token.0, token.1), vec![child]);
+    });
+}
+
+#[test]
+fn process_proposal_with_changed_tribute_subtree() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+
+        let _ = Balances::deposit_creating(&alice_address, 100);
+
+        let new_dao_account =
+            create_a_dao(&alice_address, PROPOSAL_DEPOSIT, PROPOSAL_DEPOSIT);
+
+        let token = mint_a_nft(&alice_address);
+        let child = mint_a_nft(&alice_address);
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), child.0, child.1, token.0, token.1));
+        assert_ok!(GraphModule::set_link_allowlist(alice.clone(), token.0, token.1, child.0, true));
+        assert_ok!(NFTModule::mint_non_fungible(alice.clone(), alice_address, child.0, vec![2, 3, 3], 1));
+
+        assert_ok!(DaoModule::submit_proposal(
+            alice.clone(),
+            new_dao_account.clone(),
+            alice_address,
+            1,
+            0,
+            Some(token.clone()),
+            Vec::new(),
+            Some(Vec::new())
+        ));
+        assert_ok!(DaoModule::sponsor_proposal(alice.clone(), new_dao_account.clone(), 0));
+        assert_ok!(DaoModule::vote_proposal(alice.clone(), new_dao_account.clone(), 0, true));
+
+        // anyone can link NFTs of the allowlisted collection to the escrowed tribute
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), child.0, 1, token.0, token.1));
+
+        System::set_block_number(System::block_number() + 4);
+
+        assert_ok!(DaoModule::process_proposal(alice.clone(), new_dao_account.clone(), 0));
+
+        let proposal = DaoModule::proposal(&new_dao_account, 0).unwrap();
+        assert_eq!(proposal.did_pass, false);
+        assert_eq!(DaoModule::member(&new_dao_account, &alice_address).shares, 1);
+        assert_eq!(DaoModule::tribute_subtree(&new_dao_account, 0), None);
+        assert_eq!(NFTModule::tokens(token.0, token.1).owner, alice_address);
+    });
+}
+
 #[test]
 fn sponsor_proposal() {
     new_test_ext().execute_with(|| {
@@ -821,3 +910,32 @@ fn ragequit_failed() {
         );
     });
 }
+
+#[test]
+fn migrate_to_v1_indexes_escrows() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+
+        assert_ok!(DaoModule::create_dao(
+            alice,
+            METADATA,
+            PERIOD_DURATION,
+            VOTING_PERIOD,
+            GRACE_PERIOD,
+            SHARES_REQUESTED,
+            PROPOSAL_DEPOSIT,
+            PROCESSING_REWARD,
+            DILUTION_BOUND
+        ));
+
+        let dao_account = get_last_dao_account(&alice_address, &METADATA);
+        let escrow_id = DaoModule::escrow(&dao_account);
+        // DAOs created before storage version 1 have no index.
+        crate::EscrowDAOs::<Test>::remove(&escrow_id);
+
+        DaoModule::migrate_to_v1();
+        assert_eq!(DaoModule::escrow_dao(&escrow_id), Some(dao_account));
+        assert_eq!(DaoModule::storage_version(), 1);
+    });
+}
@@ -0,0 +1,24 @@
+//! Runtime API definition for the sub pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use pallet_sub::SubTokenOrigin;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+    pub trait SubApi<AccountId, Hash, BlockNumber> where
+        AccountId: Codec,
+        Hash: Codec,
+        BlockNumber: Codec,
+    {
+        /// Get the creator, locked NFTs and SubToken type of a SubToken collection.
+        fn sub_token_origin(sub_token_collection_id: Hash) -> Option<SubTokenOrigin<AccountId, Hash, BlockNumber>>;
+        /// Get all SubToken collections ever created from a NFT.
+        fn sub_collections_of(collection_id: Hash, start_idx: u128) -> Vec<Hash>;
+        /// Get the NFTs linked to the NFTs locked for a SubToken collection.
+        fn locked_subtree(sub_token_collection_id: Hash) -> Vec<(Hash, u128)>;
+        /// Get the chain of origins from a collection up to a NFT that is not a SubToken.
+        fn provenance(collection_id: Hash) -> Vec<(Hash, SubTokenOrigin<AccountId, Hash, BlockNumber>)>;
+    }
+}
@@ -9,30 +9,90 @@
 //!
 //! * **Sub Token:** Lock NFT to this module then create new collection and tokens.
 //! * **Recover:** Restore Sub Token to NFT.
+//! * **Basket:** Several NFTs, possibly of different collections, backing one SubToken collection.
+//! * **Fixed Supply:** A SubToken collection whose whole supply was minted at creation, it can't be diluted.
 //!
 //! ## Interface
 //!
 //! ### Dispatchable Functions
 //!
 //! * `create` - Transfer NFT to this module, and then create a new collection.
-//! * `recover` - Transfer the locked NFT to the account that has all the sub tokens, and destroy the sub tokens.
+//! * `create_with_fixed_supply` - Like `create` for FTs, but the whole supply is minted to the creator at once and issuance is closed forever.
+//! * `create_basket` - Transfer several NFTs to this module, and then create a new FT collection.
+//! * `add_to_basket` - Add NFTs to a basket before the first SubToken is minted.
+//! * `recover` - Transfer the locked NFT to the account that has all the live sub tokens, and destroy the sub tokens.
 //! * `mint_non_fungible` -  Mint one or a batch of SubNFTs 
 //! * `mint_fungible` - Mint some SubFTs
+//! * `vote_reserve_price` - Vote on the lowest price the locked NFT can be bought out for
+//! * `start_buyout` - Bid for the locked NFT at or above the reserve price and start an auction
+//! * `bid_buyout` - Outbid the current bidder of an auction
+//! * `settle_buyout` - Give the NFT to the highest bidder after the auction window has passed
+//! * `propose_release` - Offer a price for the locked NFT to the SubToken holders
+//! * `approve_release` - Approve the release with the sender's SubTokens
+//! * `cancel_release` - Withdraw a release proposal
+//! * `redeem` - Burn the SubFTs of a bought-out or released NFT for a pro rata share of the proceeds
+//! * `deposit_revenue` - Deposit native currency or FTs earned by the locked NFT for the SubToken holders
+//! * `claim_revenue` - Claim the revenue accrued to the sender's SubTokens
+//!
+//! ### Buyout
+//!
+//! SubToken holders vote on a reserve price, the reserve price is the average of the votes weighted
+//! by the holders' balances and is only valid when more than half of the supply has voted. A bid at or
+//! above it starts an auction, every higher bid restarts the `BuyoutPeriod` window. Bids are reserved
+//! from the bidders. When the window passes without a higher bid, the NFT goes to the bidder, the bid
+//! is kept by this pallet and the SubTokens can no longer be minted. They can still be transferred,
+//! and burning them redeems them for their share of the bid, SubFTs are burned with `redeem`.
+//!
+//! ### Graph
+//!
+//! A graph root NFT can be locked with the NFTs linked to it, the children follow their root into
+//! this pallet and back to the account that recovers, buys out or gets the release of the NFT, with
+//! the tree intact. NFTs linked to a parent can't be locked on their own.
+//!
+//! ### Provenance
+//!
+//! The creator, locked NFTs, SubToken type and lifetime of every SubToken collection are kept in
+//! `SubTokenOrigins`, and `SubCollectionsOf` indexes the last `MaxSubCollections` SubToken
+//! collections created from a NFT. Both are kept after recovery and exposed by the runtime API with
+//! `provenance`.
+//!
+//! ### Release
+//!
+//! Holders approving with at least `ReleaseThreshold` of the supply release the locked NFT to the
+//! proposer without a recover. The proposed price is kept by this pallet and every holder, approving
+//! or not, redeems its SubTokens for a share of it like after a buyout.
+//!
+//! ### Revenue
+//!
+//! Native currency or FTs deposited for a SubToken collection increase its cumulative revenue per
+//! SubToken. A holder's revenue is settled whenever its balance changes, so SubTokens bought after a
+//! deposit don't earn it, and holders claim their settled revenue at any time.
 //!
 //! [`Call`]: ./enum.Call.html
 //! [`Config`]: ./trait.Config.html
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use codec::{Decode, Encode};
 use frame_support::{
-    decl_error, decl_event, decl_module, decl_storage, dispatch::DispatchResult, ensure,
+    decl_error, decl_event, decl_module, decl_storage,
+    dispatch::{DispatchError, DispatchResult},
+    ensure,
+    traits::{Currency, ExistenceRequirement::AllowDeath, Get, ReservableCurrency},
+    transactional,
+    weights::Weight,
 };
 use frame_system::ensure_signed;
-use sp_runtime::{traits::AccountIdConversion, ModuleId};
+use sp_runtime::{
+    helpers_128bit::multiply_by_rational,
+    traits::{AccountIdConversion, SaturatedConversion},
+    ModuleId, Perbill, RuntimeDebug,
+};
 use sp_std::vec::Vec;
 
-use pallet_collection::CollectionInterface;
-use pallet_nft::NFTInterface;
+use pallet_collection::{CollectionInterface, TokenType};
+use pallet_graph::GraphInterface;
+use pallet_nft::{KeepsTokenRanges, NFTInterface, OnTokenBurn, OnTokenMint, OnTokenTransfer};
 
 #[cfg(test)]
 mod mock;
@@ -42,18 +102,109 @@ mod tests;
 
 const PALLET_ID: ModuleId = ModuleId(*b"SubToken");
 
+/// Scale of the revenue per SubToken index.
+const REVENUE_PRECISION: u128 = 1_000_000_000_000;
+
+type BalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// Where a SubToken collection comes from.
+#[derive(Encode, Decode, Clone, PartialEq, RuntimeDebug)]
+pub struct SubTokenOrigin<AccountId, Hash, BlockNumber> {
+    pub creator: AccountId,
+    /// The locked NFTs as (collection_id, start_idx), several for a basket.
+    pub parents: Vec<(Hash, u128)>,
+    pub token_type: TokenType,
+    pub created_at: BlockNumber,
+    /// When the NFTs were recovered, bought out or released.
+    pub ended_at: Option<BlockNumber>,
+}
+
+/// The highest bid of a buyout auction.
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+pub struct Buyout<AccountId, Balance, BlockNumber> {
+    pub bidder: AccountId,
+    pub bid: Balance,
+    /// The auction can be settled after this block.
+    pub end: BlockNumber,
+}
+
+/// A proposal to release the locked NFT to `recipient` for `price`.
+#[derive(Encode, Decode, Default, Clone, PartialEq, RuntimeDebug)]
+pub struct Release<AccountId, Balance> {
+    pub recipient: AccountId,
+    pub price: Balance,
+    /// SubTokens held by the holders that approved.
+    pub approved: u128,
+}
+
+/// An asset distributed to SubToken holders.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum RevenueAsset<Hash> {
+    /// The native currency.
+    Native,
+    /// FTs of a collection.
+    Fungible(Hash),
+}
+
 pub trait Config: frame_system::Config {
     type Event: From<Event<Self>> + Into<<Self as frame_system::Config>::Event>;
     type Collection: CollectionInterface<Self::Hash, Self::AccountId>;
     type NFT: NFTInterface<Self::Hash, Self::AccountId>;
+    /// The graph of linked NFTs, a locked root NFT carries its children.
+    type Graph: GraphInterface<Self::Hash, Self::AccountId>;
+    /// The currency used to buy out locked NFTs.
+    type Currency: ReservableCurrency<Self::AccountId>;
+    /// How long a buyout auction waits for a higher bid.
+    type BuyoutPeriod: Get<Self::BlockNumber>;
+    /// The maximum number of NFTs in a basket.
+    type MaxBasketSize: Get<u32>;
+    /// The maximum number of assets distributed to the holders of a SubToken collection.
+    type MaxRevenueAssets: Get<u32>;
+    /// The maximum number of SubToken collections of a NFT kept in `SubCollectionsOf`, the oldest
+    /// ones are dropped first.
+    type MaxSubCollections: Get<u32>;
+    /// The share of the SubToken supply that must approve a release.
+    type ReleaseThreshold: Get<Perbill>;
+    /// Accounts of other pallets that hold SubTokens on behalf of their owners, e.g. exchange orders
+    /// and graph links. Like this pallet's account they don't earn revenue.
+    type PalletAccounts: Get<Vec<Self::AccountId>>;
 }
 
 decl_storage! {
     trait Store for Module<T: Config> as SubNFTModule {
-        /// The set of SubToken creators. subtoken_collection => creator
+        /// The set of SubToken creators, kept after the NFTs are sold until the collection is destroyed. subtoken_collection => creator
         pub SubTokenCreator get(fn sub_token_creator): map hasher(blake2_128_concat) T::Hash => T::AccountId;
         /// Record the collection_id of the SubToken corresponding to the locked NFT subtoken_collection => nft(collection_id, start_idx)
         pub SubTokens get(fn sub_tokens): map hasher(blake2_128_concat) T::Hash => (T::Hash, u128);
+        /// The NFTs locked for a basket. subtoken_collection => [nft(collection_id, start_idx)]
+        pub Baskets get(fn basket): map hasher(blake2_128_concat) T::Hash => Option<Vec<(T::Hash, u128)>>;
+        /// Where SubToken collections come from, kept after the NFTs are released. subtoken_collection => origin
+        pub SubTokenOrigins get(fn sub_token_origin): map hasher(blake2_128_concat) T::Hash => Option<SubTokenOrigin<T::AccountId, T::Hash, T::BlockNumber>>;
+        /// The last `MaxSubCollections` SubToken collections created from a NFT. nft(collection_id, start_idx) => [subtoken_collection]
+        pub SubCollectionsOf get(fn sub_collections_of): map hasher(blake2_128_concat) (T::Hash, u128) => Vec<T::Hash>;
+        /// SubToken collections whose supply is fixed. subtoken_collection => ()
+        pub IssuanceClosed get(fn issuance_closed): map hasher(blake2_128_concat) T::Hash => ();
+        /// The reserve price voted by SubToken holders. subtoken_collection, holder => price
+        pub ReserveVotes get(fn reserve_vote): double_map hasher(blake2_128_concat) T::Hash, hasher(blake2_128_concat) T::AccountId => Option<BalanceOf<T>>;
+        /// The sum of voted prices weighted by balance and the voted supply. subtoken_collection => (weighted_sum, voted_supply)
+        pub ReserveVoteTotals get(fn reserve_vote_totals): map hasher(blake2_128_concat) T::Hash => (u128, u128);
+        /// Running buyout auctions. subtoken_collection => buyout
+        pub Buyouts get(fn buyout): map hasher(blake2_128_concat) T::Hash => Option<Buyout<T::AccountId, BalanceOf<T>, T::BlockNumber>>;
+        /// The proceeds of settled buyouts not redeemed yet. subtoken_collection => (proceeds, supply)
+        pub BuyoutProceeds get(fn buyout_proceeds): map hasher(blake2_128_concat) T::Hash => Option<(BalanceOf<T>, u128)>;
+        /// Pending release proposals. subtoken_collection => release
+        pub Releases get(fn release): map hasher(blake2_128_concat) T::Hash => Option<Release<T::AccountId, BalanceOf<T>>>;
+        /// Holders that approved the pending release. subtoken_collection, holder => ()
+        pub ReleaseApprovals get(fn release_approval): double_map hasher(blake2_128_concat) T::Hash, hasher(blake2_128_concat) T::AccountId => ();
+        /// Assets ever deposited as revenue. subtoken_collection => [asset]
+        pub RevenueAssets get(fn revenue_assets): map hasher(blake2_128_concat) T::Hash => Vec<RevenueAsset<T::Hash>>;
+        /// Cumulative revenue per SubToken scaled by `REVENUE_PRECISION`. subtoken_collection, asset => index
+        pub RevenuePerToken get(fn revenue_per_token): double_map hasher(blake2_128_concat) T::Hash, hasher(blake2_128_concat) RevenueAsset<T::Hash> => u128;
+        /// The index a holder was last settled at. (subtoken_collection, asset), holder => index
+        pub RevenuePaid get(fn revenue_paid): double_map hasher(blake2_128_concat) (T::Hash, RevenueAsset<T::Hash>), hasher(blake2_128_concat) T::AccountId => u128;
+        /// Revenue settled to a holder and not claimed yet. (subtoken_collection, asset), holder => amount
+        pub PendingRevenue get(fn pending_revenue): double_map hasher(blake2_128_concat) (T::Hash, RevenueAsset<T::Hash>), hasher(blake2_128_concat) T::AccountId => u128;
     }
 }
 
@@ -61,19 +212,64 @@ decl_event!(
     /// Events for this module.
     pub enum Event<T>
     where
+        AccountId = <T as frame_system::Config>::AccountId,
         Hash = <T as frame_system::Config>::Hash,
+        Balance = BalanceOf<T>,
+        BlockNumber = <T as frame_system::Config>::BlockNumber,
     {
-        /// A SubCollection created. \[sub_collection_id\]
-        SubCollectionCreated(Hash),
+        /// A SubCollection created. \[sub_collection_id, creator, collection_id, token_id, sub_token_type\]
+        SubCollectionCreated(Hash, AccountId, Hash, u128, TokenType),
 
         /// Locked NFT was recovered. \[collection_id, token_id\]
         TokenRecovered(Hash, u128),
 
+        /// A SubCollection backed by a basket of NFTs was created. \[sub_collection_id, creator, nfts, sub_token_type\]
+        BasketCreated(Hash, AccountId, Vec<(Hash, u128)>, TokenType),
+
+        /// NFTs were added to a basket. \[sub_collection_id, nft_count\]
+        BasketTokensAdded(Hash, u32),
+
+        /// The whole supply of a SubToken collection was minted, no more can be minted. \[sub_collection_id, supply\]
+        IssuanceClosed(Hash, u128),
+
         /// One or a batch of SubNFTs were minted.  \[sub_collectio_idn, start_idx, end_idx\]
         SubNonFungibleTokenMinted(Hash, u128, u128),
 
         /// Some SubFTs were minted. \[sub_collection\]
         SubFungibleTokenMinted(Hash),
+
+        /// A holder voted on the reserve price. \[sub_collection, who, price\]
+        ReservePriceVoted(Hash, AccountId, Balance),
+
+        /// A buyout auction was started. \[sub_collection, bidder, bid, end\]
+        BuyoutStarted(Hash, AccountId, Balance, BlockNumber),
+
+        /// A higher bid was placed. \[sub_collection, bidder, bid, end\]
+        BuyoutBid(Hash, AccountId, Balance, BlockNumber),
+
+        /// The locked NFT was given to the highest bidder. \[sub_collection, bidder, bid\]
+        BuyoutSettled(Hash, AccountId, Balance),
+
+        /// A release of the locked NFT was proposed. \[sub_collection, recipient, price\]
+        ReleaseProposed(Hash, AccountId, Balance),
+
+        /// A holder approved the release. \[sub_collection, who, amount\]
+        ReleaseApproved(Hash, AccountId, u128),
+
+        /// The release proposal was cancelled. \[sub_collection\]
+        ReleaseCancelled(Hash),
+
+        /// The locked NFT was released to the recipient. \[sub_collection, recipient, price\]
+        Released(Hash, AccountId, Balance),
+
+        /// SubTokens were redeemed for a share of the proceeds. \[sub_collection, who, amount, share\]
+        BuyoutRedeemed(Hash, AccountId, u128, Balance),
+
+        /// Revenue was deposited for the SubToken holders. \[sub_collection, who, asset, amount\]
+        RevenueDeposited(Hash, AccountId, RevenueAsset<Hash>, u128),
+
+        /// A holder claimed revenue. \[sub_collection, who, asset, amount\]
+        RevenueClaimed(Hash, AccountId, RevenueAsset<Hash>, u128),
     }
 );
 
@@ -90,6 +286,48 @@ decl_error! {
         PermissionDenied,
         /// SubTokens cannot be burned at the time of recover
         BurnedtokensExistent,
+        /// A basket needs at least one NFT.
+        EmptyBasket,
+        /// Too many NFTs in the basket.
+        BasketTooLarge,
+        /// SubTokens were already minted.
+        AlreadyMinted,
+        /// The NFT is linked to a parent NFT, only graph root NFTs can be locked.
+        TokenLinked,
+        /// The supply of the SubToken collection is fixed.
+        IssuanceClosed,
+        /// Not enough SubTokens voted on the reserve price.
+        NoReservePrice,
+        /// The bid is lower than the reserve price or the current bid.
+        BidTooLow,
+        /// A buyout auction is running.
+        BuyoutInProgress,
+        /// There is no buyout auction.
+        BuyoutNotFound,
+        /// The auction window has passed.
+        AuctionEnded,
+        /// The auction window has not passed yet.
+        AuctionNotEnded,
+        /// The locked NFT was bought out or released, no SubTokens can be minted.
+        BoughtOut,
+        /// The locked NFT was not bought out or released.
+        NotBoughtOut,
+        /// No SubTokens to redeem.
+        NothingToRedeem,
+        /// A release is proposed.
+        ReleaseInProgress,
+        /// There is no release proposal.
+        ReleaseNotFound,
+        /// The holder already approved the release.
+        AlreadyApproved,
+        /// No revenue to deposit or claim.
+        NoRevenue,
+        /// There are no SubTokens to distribute revenue to.
+        NoHolders,
+        /// SubTokens can't be revenue of their own collection.
+        WrongRevenueAsset,
+        /// Too many assets distributed to the SubToken holders.
+        TooManyRevenueAssets,
     }
 }
 
@@ -109,68 +347,155 @@ decl_module! {
         #[weight = 10_000]
         pub fn create(origin, collection_id: T::Hash, start_idx: u128, is_fungible: bool) -> DispatchResult {
             let who = ensure_signed(origin)?;
-            // transfer function will ensure collection and token exist so don't need to re-write ensure code.
-            T::NFT::_transfer_non_fungible(who.clone(), Self::account_id(), collection_id, start_idx, 1)?;
 
-            let token = T::NFT::get_nft_token(collection_id, start_idx);
-            let sub_token_collection_id = T::Collection::_create_collection(Self::account_id(), token.uri, is_fungible)?;
+            let sub_token_collection_id = Self::lock_token(&who, collection_id, start_idx, is_fungible)?;
+            let token_type = if is_fungible { TokenType::Fungible } else { TokenType::NonFungible };
+
+            // (subtoken_collection, token owner, collection_id, token_id, subtoken_type)
+            Self::deposit_event(RawEvent::SubCollectionCreated(sub_token_collection_id, who, collection_id, start_idx, token_type));
+
+            Ok(())
+        }
+
+        /// Lock NFT to this pallet, create a new FT collection and mint its whole supply to the sender.
+        ///
+        /// No more SubTokens can ever be minted in the collection.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        ///
+        /// Parameters:
+        /// - `collection_id`: The collection in which NFT is located.
+        /// - `start_idx`: NFT's Index
+        /// - `supply`: How many SubFTs to mint.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn create_with_fixed_supply(origin, collection_id: T::Hash, start_idx: u128, supply: u128) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let sub_token_collection_id = Self::lock_token(&who, collection_id, start_idx, true)?;
+
+            let collection = T::Collection::get_collection(sub_token_collection_id);
+            T::NFT::_mint_fungible(who.clone(), sub_token_collection_id, supply, &collection)?;
+            IssuanceClosed::<T>::insert(sub_token_collection_id, ());
+
+            Self::deposit_event(RawEvent::SubCollectionCreated(sub_token_collection_id, who, collection_id, start_idx, TokenType::Fungible));
+            Self::deposit_event(RawEvent::IssuanceClosed(sub_token_collection_id, supply));
+
+            Ok(())
+        }
+
+        /// Lock several NFTs to this pallet and create a new FT collection backed by all of them.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        ///
+        /// Parameters:
+        /// - `uri`: The uri of the new collection.
+        /// - `tokens`: The NFTs to lock as (collection_id, start_idx), at most `MaxBasketSize`.
+        #[weight = 10_000 + 10_000 * (tokens.len() as Weight)]
+        #[transactional]
+        pub fn create_basket(origin, uri: Vec<u8>, tokens: Vec<(T::Hash, u128)>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(!tokens.is_empty(), Error::<T>::EmptyBasket);
+            ensure!(tokens.len() as u32 <= T::MaxBasketSize::get(), Error::<T>::BasketTooLarge);
+
+            for (collection_id, start_idx) in tokens.iter() {
+                ensure!(!T::Graph::is_linked(collection_id, *start_idx), Error::<T>::TokenLinked);
+                T::NFT::_transfer_non_fungible(who.clone(), Self::account_id(), *collection_id, *start_idx, 1)?;
+            }
+
+            let sub_token_collection_id = T::Collection::_create_collection(Self::account_id(), uri, true)?;
 
             SubTokenCreator::<T>::insert(sub_token_collection_id, &who);
-            SubTokens::<T>::insert(sub_token_collection_id, (collection_id, start_idx));
+            Baskets::<T>::insert(sub_token_collection_id, &tokens);
+            Self::record_origin(&sub_token_collection_id, &who, &tokens, TokenType::Fungible);
+
+            Self::deposit_event(RawEvent::BasketCreated(sub_token_collection_id, who, tokens, TokenType::Fungible));
+
+            Ok(())
+        }
+
+        /// Lock more NFTs into a basket before the first SubToken is minted.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must be the creator of the basket.
+        ///
+        /// Parameters:
+        /// - `sub_token_collection_id`: The collection of the basket.
+        /// - `tokens`: The NFTs to lock as (collection_id, start_idx).
+        #[weight = 10_000 + 10_000 * (tokens.len() as Weight)]
+        #[transactional]
+        pub fn add_to_basket(origin, sub_token_collection_id: T::Hash, tokens: Vec<(T::Hash, u128)>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let mut basket = Self::basket(sub_token_collection_id).ok_or(Error::<T>::SubTokenNotFound)?;
+            ensure!(Self::sub_token_creator(sub_token_collection_id) == who, Error::<T>::PermissionDenied);
+            ensure!(!T::Collection::is_minted(sub_token_collection_id), Error::<T>::AlreadyMinted);
+            ensure!(!tokens.is_empty(), Error::<T>::EmptyBasket);
+            ensure!(
+                basket.len().saturating_add(tokens.len()) as u32 <= T::MaxBasketSize::get(),
+                Error::<T>::BasketTooLarge
+            );
+
+            for (collection_id, start_idx) in tokens.iter() {
+                ensure!(!T::Graph::is_linked(collection_id, *start_idx), Error::<T>::TokenLinked);
+                T::NFT::_transfer_non_fungible(who.clone(), Self::account_id(), *collection_id, *start_idx, 1)?;
+            }
+
+            basket.extend_from_slice(&tokens);
+            Baskets::<T>::insert(sub_token_collection_id, basket);
+            Self::add_origin_parents(&sub_token_collection_id, &tokens);
 
-            // (token owner, collection_id, token_id, subtoken_collection, subtoken_type)
-            Self::deposit_event(RawEvent::SubCollectionCreated(sub_token_collection_id));
+            Self::deposit_event(RawEvent::BasketTokensAdded(sub_token_collection_id, tokens.len() as u32));
 
             Ok(())
         }
 
-        /// Burn all SubTokens and restore to NFT.
+        /// Burn all SubTokens and restore to NFT, or all NFTs of a basket.
         /// 
         /// The dispatch origin of this call must be _Signed_.
         ///
         /// Parameters:
         /// - `sub_token_collection_id`: The collection where subtokens are located.
         #[weight = 10_000]
+        #[transactional]
         pub fn recover(origin, sub_token_collection_id: T::Hash) -> DispatchResult {
-            // when collection total_supply equals 0 and burn_amount equals 0, only creator can recover
-            // if someone's balance is equal with subtoken collection total supply and burned amount equals 0, it can be recovered
+            // when collection total_supply equals 0, only creator can recover
+            // if someone's balance is equal with the live supply (total_supply excludes burned tokens), it can be recovered
             ensure!(
                 T::Collection::collection_exist(sub_token_collection_id),
                 Error::<T>::CollectionNotFound
             );
-            ensure!(SubTokens::<T>::contains_key(sub_token_collection_id), Error::<T>::SubTokenNotFound);
+            ensure!(Self::is_vault(&sub_token_collection_id), Error::<T>::SubTokenNotFound);
 
             let who = ensure_signed(origin)?;
+            ensure!(!Buyouts::<T>::contains_key(sub_token_collection_id), Error::<T>::BuyoutInProgress);
             let collection = T::Collection::get_collection(sub_token_collection_id);
 
             let balance = T::NFT::get_balance(&sub_token_collection_id, &who);
             ensure!(balance == collection.total_supply, Error::<T>::PermissionDenied);
 
-            let burned_amount = T::NFT::get_burned_amount(&sub_token_collection_id);
-            ensure!(burned_amount == 0, Error::<T>::BurnedtokensExistent);
-
             if collection.total_supply == 0 {
                 ensure!(Self::sub_token_creator(sub_token_collection_id) == who, Error::<T>::PermissionDenied);
             }
 
-            let (collection_id, start_idx) = Self::sub_tokens(sub_token_collection_id);
-            // <pallet_nft::Module<T>>::transfer_non_fungible(frame_system::RawOrigin::Signed(Self::account_id()).into(), who.clone(), collection_id, start_idx, 1)?;
-            T::NFT::_transfer_non_fungible(Self::account_id(), who, collection_id, start_idx, 1)?;
+            let locked_tokens = Self::locked_tokens(&sub_token_collection_id);
+            for (collection_id, start_idx) in locked_tokens.iter() {
+                // <pallet_nft::Module<T>>::transfer_non_fungible(frame_system::RawOrigin::Signed(Self::account_id()).into(), who.clone(), collection_id, start_idx, 1)?;
+                T::NFT::_transfer_non_fungible(Self::account_id(), who.clone(), *collection_id, *start_idx, 1)?;
+            }
 
+            Self::settle_revenue(&sub_token_collection_id, &who)?;
+            Self::remove_vault(&sub_token_collection_id);
             SubTokenCreator::<T>::remove(sub_token_collection_id);
-            SubTokens::<T>::remove(sub_token_collection_id);
+            IssuanceClosed::<T>::remove(sub_token_collection_id);
 
-            T::Collection::destory_collection(&collection_id);
-            // <pallet_collection::Collections<T>>::remove(collection_id);
+            T::NFT::destory_collection(&sub_token_collection_id, &who);
+            T::Collection::destory_collection(&sub_token_collection_id);
 
-            if collection.total_supply != 0 {
-                // <pallet_nft::Tokens<T>>::remove_prefix(sub_token_collection_id);
-                T::NFT::destory_collection(&sub_token_collection_id);
+            for (collection_id, start_idx) in locked_tokens {
+                // (collection_id, token_id)
+                Self::deposit_event(RawEvent::TokenRecovered(collection_id, start_idx));
             }
 
-            // (collection_id, token_id)
-            Self::deposit_event(RawEvent::TokenRecovered(collection_id, start_idx));
-
             Ok(())
         }
 
@@ -189,7 +514,8 @@ decl_module! {
                 T::Collection::collection_exist(sub_token_collection_id),
                 Error::<T>::CollectionNotFound
             );
-            ensure!(SubTokens::<T>::contains_key(sub_token_collection_id), Error::<T>::SubTokenNotFound);
+            ensure!(Self::is_vault(&sub_token_collection_id), Error::<T>::SubTokenNotFound);
+            ensure!(!IssuanceClosed::<T>::contains_key(sub_token_collection_id), Error::<T>::IssuanceClosed);
 
 
             let who = ensure_signed(origin)?;
@@ -222,7 +548,8 @@ decl_module! {
                 T::Collection::collection_exist(sub_token_collection_id),
                 Error::<T>::CollectionNotFound
             );
-            ensure!(SubTokens::<T>::contains_key(sub_token_collection_id), Error::<T>::SubTokenNotFound);
+            ensure!(Self::is_vault(&sub_token_collection_id), Error::<T>::SubTokenNotFound);
+            ensure!(!IssuanceClosed::<T>::contains_key(sub_token_collection_id), Error::<T>::IssuanceClosed);
 
             let who = ensure_signed(origin)?;
             let collection = T::Collection::get_collection(sub_token_collection_id);
@@ -236,6 +563,298 @@ decl_module! {
 
             Ok(())
         }
+
+        /// Vote on the reserve price of the locked NFT, weighted by the SubToken balance.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must hold SubTokens.
+        ///
+        /// Parameters:
+        /// - `sub_token_collection_id`: The collection where subtokens are located.
+        /// - `price`: The lowest price the sender accepts for the NFT.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn vote_reserve_price(origin, sub_token_collection_id: T::Hash, price: BalanceOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::is_vault(&sub_token_collection_id), Error::<T>::SubTokenNotFound);
+
+            let balance = T::NFT::get_balance(&sub_token_collection_id, &who);
+            ensure!(balance > 0, Error::<T>::PermissionDenied);
+
+            Self::remove_vote_weight(&sub_token_collection_id, &who, balance)?;
+            ReserveVotes::<T>::insert(sub_token_collection_id, &who, price);
+            Self::add_vote_weight(&sub_token_collection_id, &who, balance)?;
+
+            Self::deposit_event(RawEvent::ReservePriceVoted(sub_token_collection_id, who, price));
+
+            Ok(())
+        }
+
+        /// Bid for the locked NFT and start a buyout auction. The bid is reserved.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        ///
+        /// Parameters:
+        /// - `sub_token_collection_id`: The collection where subtokens are located.
+        /// - `bid`: The price offered for the NFT, at least the reserve price.
+        #[weight = 10_000]
+        pub fn start_buyout(origin, sub_token_collection_id: T::Hash, bid: BalanceOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::is_vault(&sub_token_collection_id), Error::<T>::SubTokenNotFound);
+            ensure!(!Buyouts::<T>::contains_key(sub_token_collection_id), Error::<T>::BuyoutInProgress);
+
+            let reserve_price = Self::reserve_price(&sub_token_collection_id).ok_or(Error::<T>::NoReservePrice)?;
+            ensure!(bid >= reserve_price, Error::<T>::BidTooLow);
+
+            T::Currency::reserve(&who, bid)?;
+
+            let end = <frame_system::Pallet<T>>::block_number() + T::BuyoutPeriod::get();
+            Buyouts::<T>::insert(sub_token_collection_id, Buyout {
+                bidder: who.clone(),
+                bid,
+                end,
+            });
+
+            Self::deposit_event(RawEvent::BuyoutStarted(sub_token_collection_id, who, bid, end));
+
+            Ok(())
+        }
+
+        /// Outbid the current bidder, the auction window restarts.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        ///
+        /// Parameters:
+        /// - `sub_token_collection_id`: The collection where subtokens are located.
+        /// - `bid`: The price offered for the NFT, higher than the current bid.
+        #[weight = 10_000]
+        pub fn bid_buyout(origin, sub_token_collection_id: T::Hash, bid: BalanceOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            let buyout = Self::buyout(sub_token_collection_id).ok_or(Error::<T>::BuyoutNotFound)?;
+            let now = <frame_system::Pallet<T>>::block_number();
+            ensure!(now <= buyout.end, Error::<T>::AuctionEnded);
+            ensure!(bid > buyout.bid, Error::<T>::BidTooLow);
+
+            T::Currency::reserve(&who, bid)?;
+            T::Currency::unreserve(&buyout.bidder, buyout.bid);
+
+            let end = now + T::BuyoutPeriod::get();
+            Buyouts::<T>::insert(sub_token_collection_id, Buyout {
+                bidder: who.clone(),
+                bid,
+                end,
+            });
+
+            Self::deposit_event(RawEvent::BuyoutBid(sub_token_collection_id, who, bid, end));
+
+            Ok(())
+        }
+
+        /// Give the locked NFT or basket to the highest bidder after the auction window has passed.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        ///
+        /// Parameters:
+        /// - `sub_token_collection_id`: The collection where subtokens are located.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn settle_buyout(origin, sub_token_collection_id: T::Hash) -> DispatchResult {
+            ensure_signed(origin)?;
+
+            let buyout = Self::buyout(sub_token_collection_id).ok_or(Error::<T>::BuyoutNotFound)?;
+            ensure!(<frame_system::Pallet<T>>::block_number() > buyout.end, Error::<T>::AuctionNotEnded);
+
+            Buyouts::<T>::remove(sub_token_collection_id);
+            T::Currency::unreserve(&buyout.bidder, buyout.bid);
+            Self::sell_vault(&sub_token_collection_id, &buyout.bidder, buyout.bid)?;
+
+            Self::deposit_event(RawEvent::BuyoutSettled(sub_token_collection_id, buyout.bidder, buyout.bid));
+
+            Ok(())
+        }
+
+        /// Propose to release the locked NFT to the sender for `price`. The price is reserved.
+        ///
+        /// A proposal with a higher price replaces the pending one, whose price is unreserved. The
+        /// approvals are kept, holders that approved a price also approve a higher one.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        ///
+        /// Parameters:
+        /// - `sub_token_collection_id`: The collection where subtokens are located.
+        /// - `price`: Paid to the SubToken holders pro rata when the release is approved.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn propose_release(origin, sub_token_collection_id: T::Hash, price: BalanceOf<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::is_vault(&sub_token_collection_id), Error::<T>::SubTokenNotFound);
+            let approved = match Self::release(sub_token_collection_id) {
+                Some(pending) => {
+                    ensure!(price > pending.price, Error::<T>::ReleaseInProgress);
+                    T::Currency::unreserve(&pending.recipient, pending.price);
+                    Self::deposit_event(RawEvent::ReleaseCancelled(sub_token_collection_id));
+                    pending.approved
+                }
+                None => 0,
+            };
+
+            T::Currency::reserve(&who, price)?;
+            Releases::<T>::insert(sub_token_collection_id, Release {
+                recipient: who.clone(),
+                price,
+                approved,
+            });
+
+            Self::deposit_event(RawEvent::ReleaseProposed(sub_token_collection_id, who, price));
+
+            Ok(())
+        }
+
+        /// Approve the pending release with the sender's SubTokens. The locked NFT is released when
+        /// the approvals reach `ReleaseThreshold` of the supply.
+        ///
+        /// The dispatch origin of this call must be _Signed_ and the sender must hold SubTokens.
+        ///
+        /// Parameters:
+        /// - `sub_token_collection_id`: The collection where subtokens are located.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn approve_release(origin, sub_token_collection_id: T::Hash) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let mut release = Self::release(sub_token_collection_id).ok_or(Error::<T>::ReleaseNotFound)?;
+            ensure!(!ReleaseApprovals::<T>::contains_key(sub_token_collection_id, &who), Error::<T>::AlreadyApproved);
+
+            let balance = T::NFT::get_balance(&sub_token_collection_id, &who);
+            ensure!(balance > 0, Error::<T>::PermissionDenied);
+
+            ReleaseApprovals::<T>::insert(sub_token_collection_id, &who, ());
+            release.approved = release.approved.saturating_add(balance);
+            Self::deposit_event(RawEvent::ReleaseApproved(sub_token_collection_id, who, balance));
+
+            let supply = T::Collection::get_collection(sub_token_collection_id).total_supply;
+            if release.approved < T::ReleaseThreshold::get() * supply {
+                Releases::<T>::insert(sub_token_collection_id, release);
+                return Ok(());
+            }
+
+            ensure!(!Buyouts::<T>::contains_key(sub_token_collection_id), Error::<T>::BuyoutInProgress);
+            Releases::<T>::remove(sub_token_collection_id);
+            T::Currency::unreserve(&release.recipient, release.price);
+            Self::sell_vault(&sub_token_collection_id, &release.recipient, release.price)?;
+
+            Self::deposit_event(RawEvent::Released(sub_token_collection_id, release.recipient, release.price));
+
+            Ok(())
+        }
+
+        /// Cancel the release proposed by the sender and unreserve the price.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        ///
+        /// Parameters:
+        /// - `sub_token_collection_id`: The collection where subtokens are located.
+        #[weight = 10_000]
+        pub fn cancel_release(origin, sub_token_collection_id: T::Hash) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let release = Self::release(sub_token_collection_id).ok_or(Error::<T>::ReleaseNotFound)?;
+            ensure!(release.recipient == who, Error::<T>::PermissionDenied);
+
+            Self::remove_release(&sub_token_collection_id);
+
+            Self::deposit_event(RawEvent::ReleaseCancelled(sub_token_collection_id));
+
+            Ok(())
+        }
+
+        /// Burn all SubFTs of the sender for a pro rata share of the buyout or release proceeds.
+        ///
+        /// SubNFTs are redeemed by burning them with `burn_non_fungible` of pallet-nft.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        ///
+        /// Parameters:
+        /// - `sub_token_collection_id`: The collection where subtokens are located.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn redeem(origin, sub_token_collection_id: T::Hash) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(BuyoutProceeds::<T>::contains_key(sub_token_collection_id), Error::<T>::NotBoughtOut);
+
+            let balance = T::NFT::get_balance(&sub_token_collection_id, &who);
+            ensure!(balance > 0, Error::<T>::NothingToRedeem);
+
+            // The share is paid by `on_burn`.
+            T::NFT::_burn_fungible(who, sub_token_collection_id, balance)
+        }
+
+        /// Deposit revenue of the locked NFT, it is shared by the current SubToken holders pro rata.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        ///
+        /// Parameters:
+        /// - `sub_token_collection_id`: The collection where subtokens are located.
+        /// - `asset`: The native currency or the FT collection paid.
+        /// - `amount`: How much to deposit.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn deposit_revenue(origin, sub_token_collection_id: T::Hash, asset: RevenueAsset<T::Hash>, amount: u128) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::is_vault(&sub_token_collection_id), Error::<T>::SubTokenNotFound);
+            ensure!(amount > 0, Error::<T>::NoRevenue);
+            ensure!(asset != RevenueAsset::Fungible(sub_token_collection_id), Error::<T>::WrongRevenueAsset);
+
+            let supply = Self::revenue_supply(&sub_token_collection_id);
+            ensure!(supply > 0, Error::<T>::NoHolders);
+
+            let mut assets = Self::revenue_assets(sub_token_collection_id);
+            if !assets.contains(&asset) {
+                ensure!((assets.len() as u32) < T::MaxRevenueAssets::get(), Error::<T>::TooManyRevenueAssets);
+                assets.push(asset);
+                RevenueAssets::<T>::insert(sub_token_collection_id, assets);
+            }
+
+            Self::pay_revenue(&asset, who.clone(), Self::account_id(), amount)?;
+
+            let increase = multiply_by_rational(amount, REVENUE_PRECISION, supply).map_err(|_| Error::<T>::NumOverflow)?;
+            let index = Self::revenue_per_token(sub_token_collection_id, asset)
+                .checked_add(increase)
+                .ok_or(Error::<T>::NumOverflow)?;
+            RevenuePerToken::<T>::insert(sub_token_collection_id, asset, index);
+
+            Self::deposit_event(RawEvent::RevenueDeposited(sub_token_collection_id, who, asset, amount));
+
+            Ok(())
+        }
+
+        /// Claim the revenue accrued to the sender's SubTokens.
+        ///
+        /// The dispatch origin of this call must be _Signed_.
+        ///
+        /// Parameters:
+        /// - `sub_token_collection_id`: The collection where subtokens are located.
+        /// - `asset`: The native currency or the FT collection to claim.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn claim_revenue(origin, sub_token_collection_id: T::Hash, asset: RevenueAsset<T::Hash>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            Self::settle_revenue(&sub_token_collection_id, &who)?;
+            let amount = PendingRevenue::<T>::take((sub_token_collection_id, asset), &who);
+            ensure!(amount > 0, Error::<T>::NoRevenue);
+
+            Self::pay_revenue(&asset, Self::account_id(), who.clone(), amount)?;
+
+            Self::deposit_event(RawEvent::RevenueClaimed(sub_token_collection_id, who, asset, amount));
+
+            Ok(())
+        }
+    }
+}
+
+impl<T: Config> KeepsTokenRanges<T::Hash, T::AccountId> for Module<T> {
+    /// Locked NFTs are referenced by their start_idx.
+    fn keeps_range(_collection_id: &T::Hash, _start_idx: u128, owner: &T::AccountId) -> bool {
+        *owner == Self::account_id()
     }
 }
 
@@ -244,4 +863,316 @@ impl<T: Config> Module<T> {
     pub fn account_id() -> T::AccountId {
         PALLET_ID.into_account()
     }
+    /// Whether the collection is a SubToken collection backed by a locked NFT or a basket.
+    pub fn is_vault(sub_token_collection_id: &T::Hash) -> bool {
+        SubTokens::<T>::contains_key(sub_token_collection_id) || Baskets::<T>::contains_key(sub_token_collection_id)
+    }
+
+    /// The NFTs locked for a SubToken collection.
+    pub fn locked_tokens(sub_token_collection_id: &T::Hash) -> Vec<(T::Hash, u128)> {
+        if let Some(basket) = Self::basket(sub_token_collection_id) {
+            basket
+        } else if SubTokens::<T>::contains_key(sub_token_collection_id) {
+            sp_std::vec![Self::sub_tokens(sub_token_collection_id)]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// The NFTs linked to the locked NFTs of a SubToken collection, which are released with them.
+    pub fn locked_subtree(sub_token_collection_id: &T::Hash) -> Vec<(T::Hash, u128)> {
+        let mut subtree = Vec::new();
+        for (collection_id, start_idx) in Self::locked_tokens(sub_token_collection_id) {
+            subtree.extend(T::Graph::descendants(&collection_id, start_idx));
+        }
+        subtree
+    }
+
+    /// Lock a NFT of `who` and create the SubToken collection owned by this pallet.
+    fn lock_token(who: &T::AccountId, collection_id: T::Hash, start_idx: u128, is_fungible: bool) -> Result<T::Hash, DispatchError> {
+        // a linked NFT is owned by the graph pallet, its root NFT must be locked instead.
+        ensure!(!T::Graph::is_linked(&collection_id, start_idx), Error::<T>::TokenLinked);
+        // transfer function will ensure collection and token exist so don't need to re-write ensure code.
+        // children of a graph root NFT follow their root, so the whole subtree is locked with it.
+        T::NFT::_transfer_non_fungible(who.clone(), Self::account_id(), collection_id, start_idx, 1)?;
+
+        let token = T::NFT::get_nft_token(collection_id, start_idx);
+        let sub_token_collection_id = T::Collection::_create_collection(Self::account_id(), token.uri, is_fungible)?;
+
+        SubTokenCreator::<T>::insert(sub_token_collection_id, who);
+        SubTokens::<T>::insert(sub_token_collection_id, (collection_id, start_idx));
+        let token_type = if is_fungible { TokenType::Fungible } else { TokenType::NonFungible };
+        Self::record_origin(&sub_token_collection_id, who, &[(collection_id, start_idx)], token_type);
+
+        Ok(sub_token_collection_id)
+    }
+
+    fn record_origin(sub_token_collection_id: &T::Hash, creator: &T::AccountId, parents: &[(T::Hash, u128)], token_type: TokenType) {
+        SubTokenOrigins::<T>::insert(sub_token_collection_id, SubTokenOrigin {
+            creator: creator.clone(),
+            parents: Vec::new(),
+            token_type,
+            created_at: <frame_system::Pallet<T>>::block_number(),
+            ended_at: None,
+        });
+        Self::add_origin_parents(sub_token_collection_id, parents);
+    }
+
+    fn add_origin_parents(sub_token_collection_id: &T::Hash, parents: &[(T::Hash, u128)]) {
+        SubTokenOrigins::<T>::mutate(sub_token_collection_id, |origin| {
+            if let Some(origin) = origin {
+                origin.parents.extend_from_slice(parents);
+            }
+        });
+        let max = T::MaxSubCollections::get() as usize;
+        for parent in parents {
+            SubCollectionsOf::<T>::mutate(parent, |sub_collections| {
+                sub_collections.push(*sub_token_collection_id);
+                // A NFT is locked for one SubToken collection at a time, so only the last one may
+                // still be live.
+                if sub_collections.len() > max {
+                    let excess = sub_collections.len() - max;
+                    sub_collections.drain(..excess);
+                }
+            });
+        }
+    }
+
+    /// The chain of origins from `collection_id` up to a NFT that is not a SubToken, following the
+    /// first parent of baskets.
+    pub fn provenance(collection_id: T::Hash) -> Vec<(T::Hash, SubTokenOrigin<T::AccountId, T::Hash, T::BlockNumber>)> {
+        let mut chain = Vec::new();
+        let mut next = Some(collection_id);
+        while let Some(collection_id) = next {
+            next = None;
+            if let Some(origin) = Self::sub_token_origin(collection_id) {
+                next = origin.parents.first().map(|(parent_collection_id, _)| *parent_collection_id);
+                chain.push((collection_id, origin));
+            }
+        }
+        chain
+    }
+
+    /// Remove the records of a SubToken collection whose NFTs were released.
+    fn remove_vault(sub_token_collection_id: &T::Hash) {
+        SubTokenOrigins::<T>::mutate(sub_token_collection_id, |origin| {
+            if let Some(origin) = origin {
+                origin.ended_at = Some(<frame_system::Pallet<T>>::block_number());
+            }
+        });
+        SubTokens::<T>::remove(sub_token_collection_id);
+        Baskets::<T>::remove(sub_token_collection_id);
+        ReserveVotes::<T>::remove_prefix(sub_token_collection_id);
+        ReserveVoteTotals::<T>::remove(sub_token_collection_id);
+        Self::remove_release(sub_token_collection_id);
+    }
+
+    /// Remove the release proposal and unreserve its price.
+    fn remove_release(sub_token_collection_id: &T::Hash) {
+        if let Some(release) = Releases::<T>::take(sub_token_collection_id) {
+            T::Currency::unreserve(&release.recipient, release.price);
+        }
+        ReleaseApprovals::<T>::remove_prefix(sub_token_collection_id);
+    }
+
+    /// Give the locked NFTs to `buyer` for `price`, the SubTokens can then only be redeemed for the price.
+    ///
+    /// `price` must be free balance of `buyer`.
+    fn sell_vault(sub_token_collection_id: &T::Hash, buyer: &T::AccountId, price: BalanceOf<T>) -> DispatchResult {
+        for (collection_id, start_idx) in Self::locked_tokens(sub_token_collection_id) {
+            T::NFT::_transfer_non_fungible(Self::account_id(), buyer.clone(), collection_id, start_idx, 1)?;
+        }
+
+        T::Currency::transfer(buyer, &Self::account_id(), price, AllowDeath)?;
+
+        let supply = T::Collection::get_collection(*sub_token_collection_id).total_supply;
+        BuyoutProceeds::<T>::insert(sub_token_collection_id, (price, supply));
+        Self::remove_vault(sub_token_collection_id);
+
+        Ok(())
+    }
+
+    fn add_release_weight(sub_token_collection_id: &T::Hash, who: &T::AccountId, amount: u128) {
+        if ReleaseApprovals::<T>::contains_key(sub_token_collection_id, who) {
+            Releases::<T>::mutate(sub_token_collection_id, |release| {
+                if let Some(release) = release {
+                    release.approved = release.approved.saturating_add(amount);
+                }
+            });
+        }
+    }
+
+    fn remove_release_weight(sub_token_collection_id: &T::Hash, who: &T::AccountId, amount: u128) {
+        if ReleaseApprovals::<T>::contains_key(sub_token_collection_id, who) {
+            Releases::<T>::mutate(sub_token_collection_id, |release| {
+                if let Some(release) = release {
+                    release.approved = release.approved.saturating_sub(amount);
+                }
+            });
+        }
+    }
+
+    /// The reserve price, if more than half of the SubToken supply has voted.
+    pub fn reserve_price(sub_token_collection_id: &T::Hash) -> Option<BalanceOf<T>> {
+        let (weighted_sum, voted_supply) = Self::reserve_vote_totals(sub_token_collection_id);
+        let supply = T::Collection::get_collection(*sub_token_collection_id).total_supply;
+        if voted_supply == 0 || voted_supply <= supply / 2 {
+            return None;
+        }
+
+        Some((weighted_sum / voted_supply).saturated_into())
+    }
+
+    /// Pay `who` the share of the buyout or release proceeds of `amount` SubTokens being burned.
+    fn redeem_share(sub_token_collection_id: &T::Hash, who: &T::AccountId, amount: u128) -> DispatchResult {
+        let (proceeds, supply) = Self::buyout_proceeds(sub_token_collection_id).ok_or(Error::<T>::NotBoughtOut)?;
+        ensure!(supply > 0, Error::<T>::NothingToRedeem);
+
+        let proceeds_value = proceeds.saturated_into::<u128>();
+        let share = if amount >= supply {
+            proceeds_value
+        } else {
+            multiply_by_rational(proceeds_value, amount, supply).map_err(|_| Error::<T>::NumOverflow)?
+        };
+        let share = share.saturated_into::<BalanceOf<T>>();
+
+        T::Currency::transfer(&Self::account_id(), who, share, AllowDeath)?;
+
+        BuyoutProceeds::<T>::insert(sub_token_collection_id, (proceeds - share, supply.saturating_sub(amount)));
+
+        Self::deposit_event(RawEvent::BuyoutRedeemed(*sub_token_collection_id, who.clone(), amount, share));
+
+        Ok(())
+    }
+
+    fn add_vote_weight(sub_token_collection_id: &T::Hash, who: &T::AccountId, amount: u128) -> DispatchResult {
+        if let Some(price) = Self::reserve_vote(sub_token_collection_id, who) {
+            let weight = price.saturated_into::<u128>().checked_mul(amount).ok_or(Error::<T>::NumOverflow)?;
+            let (weighted_sum, voted_supply) = Self::reserve_vote_totals(sub_token_collection_id);
+            let weighted_sum = weighted_sum.checked_add(weight).ok_or(Error::<T>::NumOverflow)?;
+            let voted_supply = voted_supply.checked_add(amount).ok_or(Error::<T>::NumOverflow)?;
+            ReserveVoteTotals::<T>::insert(sub_token_collection_id, (weighted_sum, voted_supply));
+        }
+        Ok(())
+    }
+
+    fn remove_vote_weight(sub_token_collection_id: &T::Hash, who: &T::AccountId, amount: u128) -> DispatchResult {
+        if let Some(price) = Self::reserve_vote(sub_token_collection_id, who) {
+            let weight = price.saturated_into::<u128>().checked_mul(amount).ok_or(Error::<T>::NumOverflow)?;
+            let (weighted_sum, voted_supply) = Self::reserve_vote_totals(sub_token_collection_id);
+            let weighted_sum = weighted_sum.checked_sub(weight).ok_or(Error::<T>::NumOverflow)?;
+            let voted_supply = voted_supply.checked_sub(amount).ok_or(Error::<T>::NumOverflow)?;
+            ReserveVoteTotals::<T>::insert(sub_token_collection_id, (weighted_sum, voted_supply));
+        }
+        Ok(())
+    }
+
+    /// Whether SubTokens held by `who` earn revenue, those held by pallets for their owners don't.
+    fn earns_revenue(who: &T::AccountId) -> bool {
+        *who != Self::account_id() && !T::PalletAccounts::get().contains(who)
+    }
+
+    /// The SubToken supply that earns revenue.
+    fn revenue_supply(sub_token_collection_id: &T::Hash) -> u128 {
+        let supply = T::Collection::get_collection(*sub_token_collection_id).total_supply;
+        let held = T::PalletAccounts::get()
+            .iter()
+            .chain(sp_std::iter::once(&Self::account_id()))
+            .fold(0u128, |held, who| held.saturating_add(T::NFT::get_balance(sub_token_collection_id, who)));
+
+        supply.saturating_sub(held)
+    }
+
+    /// Move the revenue accrued to the current balance of `who` to its pending revenue.
+    ///
+    /// Must be called before the balance changes, so SubTokens received later don't earn earlier revenue.
+    fn settle_revenue(sub_token_collection_id: &T::Hash, who: &T::AccountId) -> DispatchResult {
+        let assets = Self::revenue_assets(sub_token_collection_id);
+        if assets.is_empty() || !Self::earns_revenue(who) {
+            return Ok(());
+        }
+
+        let balance = T::NFT::get_balance(sub_token_collection_id, who);
+        for asset in assets {
+            let key = (*sub_token_collection_id, asset);
+            let index = Self::revenue_per_token(sub_token_collection_id, asset);
+            let paid = Self::revenue_paid(&key, who);
+            if index == paid {
+                continue;
+            }
+
+            let accrued = multiply_by_rational(balance, index - paid, REVENUE_PRECISION).map_err(|_| Error::<T>::NumOverflow)?;
+            if accrued > 0 {
+                let pending = Self::pending_revenue(&key, who)
+                    .checked_add(accrued)
+                    .ok_or(Error::<T>::NumOverflow)?;
+                PendingRevenue::<T>::insert(&key, who, pending);
+            }
+            RevenuePaid::<T>::insert(&key, who, index);
+        }
+
+        Ok(())
+    }
+
+    fn pay_revenue(asset: &RevenueAsset<T::Hash>, from: T::AccountId, to: T::AccountId, amount: u128) -> DispatchResult {
+        match asset {
+            RevenueAsset::Native => T::Currency::transfer(&from, &to, amount.saturated_into(), AllowDeath),
+            RevenueAsset::Fungible(collection_id) => T::NFT::_transfer_fungible(from, to, *collection_id, amount),
+        }
+    }
+}
+
+/// Votes, approvals and revenue follow the SubToken balances. SubTokens of a bought-out NFT can't be
+/// minted, and burning them pays their share, so the remaining proceeds always belong to the
+/// remaining SubTokens.
+///
+/// The hooks run for every collection, so other collections return after one read.
+impl<T: Config> OnTokenMint<T::Hash, T::AccountId> for Module<T> {
+    fn on_mint(collection_id: &T::Hash, _range: Option<(u128, u128)>, to: &T::AccountId, amount: u128) -> DispatchResult {
+        if !SubTokenCreator::<T>::contains_key(collection_id) {
+            return Ok(());
+        }
+        ensure!(!BuyoutProceeds::<T>::contains_key(collection_id), Error::<T>::BoughtOut);
+        ensure!(!IssuanceClosed::<T>::contains_key(collection_id), Error::<T>::IssuanceClosed);
+        Self::settle_revenue(collection_id, to)?;
+        Self::add_vote_weight(collection_id, to, amount)?;
+        Self::add_release_weight(collection_id, to, amount);
+        Ok(())
+    }
+}
+
+impl<T: Config> OnTokenTransfer<T::Hash, T::AccountId> for Module<T> {
+    fn on_transfer(
+        collection_id: &T::Hash,
+        _range: Option<(u128, u128)>,
+        from: &T::AccountId,
+        to: &T::AccountId,
+        amount: u128,
+    ) -> DispatchResult {
+        if !SubTokenCreator::<T>::contains_key(collection_id) {
+            return Ok(());
+        }
+        Self::settle_revenue(collection_id, from)?;
+        Self::settle_revenue(collection_id, to)?;
+        Self::remove_vote_weight(collection_id, from, amount)?;
+        Self::add_vote_weight(collection_id, to, amount)?;
+        Self::remove_release_weight(collection_id, from, amount);
+        Self::add_release_weight(collection_id, to, amount);
+        Ok(())
+    }
+}
+
+impl<T: Config> OnTokenBurn<T::Hash, T::AccountId> for Module<T> {
+    fn on_burn(collection_id: &T::Hash, _range: Option<(u128, u128)>, from: &T::AccountId, amount: u128) -> DispatchResult {
+        if !SubTokenCreator::<T>::contains_key(collection_id) {
+            return Ok(());
+        }
+        Self::settle_revenue(collection_id, from)?;
+        Self::remove_vote_weight(collection_id, from, amount)?;
+        Self::remove_release_weight(collection_id, from, amount);
+        if BuyoutProceeds::<T>::contains_key(collection_id) {
+            Self::redeem_share(collection_id, from, amount)?;
+        }
+        Ok(())
+    }
 }
@@ -3,7 +3,7 @@ use pallet_nft as pallet_nft;
 use sp_core::H256;
 use frame_support::parameter_types;
 use sp_runtime::{
-	traits::{BlakeTwo256, IdentityLookup}, testing::Header,
+	traits::{BlakeTwo256, IdentityLookup}, testing::Header, Perbill,
 };
 use frame_system as system;
 use frame_support::{
@@ -21,9 +21,11 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Module, Call, Config, Storage, Event<T>},
-		CollectionModule: pallet_collection::{Module, Call, Storage, Event<T>},
+		Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
+	CollectionModule: pallet_collection::{Module, Call, Storage, Event<T>},
 		NFTModule: pallet_nft::{Module, Call, Storage, Event<T>},
 		SubNFTModule: pallet_sub::{Module, Call, Storage, Event<T>},
+		GraphModule: pallet_graph::{Module, Call, Storage, Event<T>},
 	}
 );
 
@@ -50,30 +52,92 @@ impl system::Config for Test {
 	type BlockHashCount = BlockHashCount;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
 	type SS58Prefix = SS58Prefix;
 }
 
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+	pub const CollectionDeposit: u64 = 0;
+	pub const StringLimit: u32 = 50;
+	pub const MaxBatchTransfers: u32 = 10;
+	pub const BuyoutPeriod: u64 = 10;
+	pub const MaxBasketSize: u32 = 3;
+	pub const MaxRevenueAssets: u32 = 2;
+	pub const MaxSubCollections: u32 = 2;
+	pub const ReleaseThreshold: Perbill = Perbill::from_percent(75);
+	pub const MaxEquippables: u32 = 2;
+	pub const MaxDepth: u32 = 6;
+	pub const MaxDescendants: u32 = 5;
+	pub const MaxTokenLocks: u32 = 3;
+	pub PalletAccounts: Vec<u64> = vec![GraphModule::account_id()];
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
 impl pallet_collection::Config for Test {
 	type Event = Event;
 	type RandomnessSource = TestRandomness;
+	type Currency = Balances;
+	type CollectionDeposit = CollectionDeposit;
+	type StringLimit = StringLimit;
 }
 
 impl pallet_nft::Config for Test {
 	type Event = Event;
 	type Collection = CollectionModule;
+	type OnTransfer = SubNFTModule;
+	type OnMint = SubNFTModule;
+	type OnBurn = (GraphModule, SubNFTModule);
+	type MaxBatchTransfers = MaxBatchTransfers;
+	type Currency = Balances;
+	type Signature = sp_runtime::testing::TestSignature;
+	type Signer = sp_runtime::testing::UintAuthorityId;
+	type RangeKeepers = (GraphModule, SubNFTModule);
+	type ForceOrigin = frame_system::EnsureRoot<u64>;
+	type MaxTokenLocks = MaxTokenLocks;
 }
 
 impl pallet_sub::Config for Test {
 	type Event = Event;
 	type Collection = CollectionModule;
 	type NFT = NFTModule;
+	type Graph = GraphModule;
+	type Currency = Balances;
+	type BuyoutPeriod = BuyoutPeriod;
+	type MaxBasketSize = MaxBasketSize;
+	type MaxRevenueAssets = MaxRevenueAssets;
+	type MaxSubCollections = MaxSubCollections;
+	type ReleaseThreshold = ReleaseThreshold;
+	type PalletAccounts = PalletAccounts;
+}
+
+impl pallet_graph::Config for Test {
+	type Event = Event;
+	type Collection = CollectionModule;
+	type NFT = NFTModule;
+	type StringLimit = StringLimit;
+	type MaxEquippables = MaxEquippables;
+	type MaxDepth = MaxDepth;
+	type MaxDescendants = MaxDescendants;
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 1000), (2, 1000), (3, 1000)],
+	}.assimilate_storage(&mut t).unwrap();
+	t.into()
 }
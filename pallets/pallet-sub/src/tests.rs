@@ -1,7 +1,8 @@
-use crate::{mock::*, Error};
+use crate::{mock::*, Error, RevenueAsset};
 use frame_support::{assert_noop, assert_ok};
 
-use pallet_collection::CollectionInterface;
+use pallet_collection::{CollectionInterface, TokenType};
+use pallet_nft::NFTInterface;
 
 use sp_core::H256;
 
@@ -136,6 +137,29 @@ fn recover_success() {
     });
 }
 
+#[test]
+fn recover_with_burned_tokens_success() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 1).unwrap();
+
+        assert_ok!(SubNFTModule::create(alice.clone(), collection_id, 0, true));
+        let nonce = CollectionModule::get_nonce();
+        let sub_token_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+
+        assert_ok!(SubNFTModule::mint_fungible(alice.clone(), alice_address, sub_token_collection_id, 100));
+        assert_ok!(NFTModule::burn_fungible(alice.clone(), sub_token_collection_id, 10));
+
+        assert_ok!(SubNFTModule::recover(alice, sub_token_collection_id));
+        assert_eq!(NFTModule::tokens(collection_id, 0).owner, alice_address);
+    });
+}
+
 #[test]
 fn recover_failed() {
     new_test_ext().execute_with(|| {
@@ -205,9 +229,10 @@ fn recover_failed() {
             burn_amount,
         )
         .unwrap();
+        NFTModule::transfer_non_fungible(alice.clone(), bob_address, sub_token_collection_id, sub_token_start_idx + burn_amount, 1).unwrap();
         assert_noop!(
             SubNFTModule::recover(alice, sub_token_collection_id),
-            Error::<Test>::BurnedtokensExistent
+            Error::<Test>::PermissionDenied
         );
     });
 }
@@ -455,3 +480,727 @@ fn mint_fungible_failed() {
         );
     });
 }
+
+#[test]
+fn buyout_success() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        let bob_address = 2;
+        let bob = Origin::signed(bob_address);
+        let carol_address = 3;
+        let carol = Origin::signed(carol_address);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 1).unwrap();
+
+        assert_ok!(SubNFTModule::create(alice.clone(), collection_id, 0, true));
+        let nonce = CollectionModule::get_nonce();
+        let sub_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        assert_ok!(SubNFTModule::mint_fungible(alice.clone(), alice_address, sub_collection_id, 60));
+        assert_ok!(SubNFTModule::mint_fungible(alice.clone(), bob_address, sub_collection_id, 40));
+
+        assert_ok!(SubNFTModule::vote_reserve_price(alice.clone(), sub_collection_id, 100));
+        assert_ok!(SubNFTModule::vote_reserve_price(bob.clone(), sub_collection_id, 200));
+        assert_eq!(SubNFTModule::reserve_price(&sub_collection_id), Some(140));
+
+        // votes follow the balances
+        assert_ok!(NFTModule::transfer_fungible(alice.clone(), carol_address, sub_collection_id, 20));
+        assert_eq!(SubNFTModule::reserve_price(&sub_collection_id), Some(150));
+
+        assert_ok!(SubNFTModule::start_buyout(carol.clone(), sub_collection_id, 150));
+        assert_eq!(Balances::reserved_balance(carol_address), 150);
+
+        assert_ok!(SubNFTModule::bid_buyout(bob.clone(), sub_collection_id, 200));
+        assert_eq!(Balances::reserved_balance(carol_address), 0);
+        assert_eq!(Balances::reserved_balance(bob_address), 200);
+
+        System::set_block_number(11);
+        assert_ok!(SubNFTModule::settle_buyout(carol.clone(), sub_collection_id));
+        assert_eq!(NFTModule::tokens(collection_id, 0).owner, bob_address);
+        assert_eq!(Balances::free_balance(bob_address), 800);
+        let sub_collection = CollectionModule::collections(sub_collection_id);
+        assert_noop!(
+            <NFTModule as NFTInterface<_, _>>::_mint_fungible(alice_address, sub_collection_id, 1, &sub_collection),
+            Error::<Test>::BoughtOut
+        );
+        // SubTokens can still move after the buyout
+        assert_ok!(NFTModule::transfer_fungible(alice.clone(), carol_address, sub_collection_id, 10));
+
+        assert_ok!(SubNFTModule::redeem(alice.clone(), sub_collection_id));
+        assert_eq!(NFTModule::address_balances((sub_collection_id, alice_address)), 0);
+        assert_eq!(CollectionModule::collections(sub_collection_id).total_supply, 70);
+
+        // burning SubTokens redeems them too
+        assert_ok!(NFTModule::burn_fungible(carol.clone(), sub_collection_id, 10));
+        assert_eq!(Balances::free_balance(carol_address), 1020);
+        assert_ok!(NFTModule::transfer_fungible(carol.clone(), alice_address, sub_collection_id, 10));
+        assert_ok!(SubNFTModule::redeem(alice.clone(), sub_collection_id));
+        assert_ok!(SubNFTModule::redeem(carol, sub_collection_id));
+        assert_ok!(SubNFTModule::redeem(bob, sub_collection_id));
+        assert_eq!(Balances::free_balance(alice_address), 1080);
+        assert_eq!(Balances::free_balance(carol_address), 1040);
+        assert_eq!(Balances::free_balance(bob_address), 880);
+        assert_eq!(Balances::free_balance(SubNFTModule::account_id()), 0);
+        assert_eq!(CollectionModule::collections(sub_collection_id).total_supply, 0);
+
+        assert_noop!(
+            SubNFTModule::redeem(alice, sub_collection_id),
+            Error::<Test>::NothingToRedeem
+        );
+    });
+}
+
+#[test]
+fn buyout_large_supply() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        let bob_address = 2;
+        let bob = Origin::signed(bob_address);
+        let carol_address = 3;
+        let carol = Origin::signed(carol_address);
+        let half = 1u128 << 100;
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 1).unwrap();
+
+        assert_ok!(SubNFTModule::create(alice.clone(), collection_id, 0, true));
+        let nonce = CollectionModule::get_nonce();
+        let sub_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        assert_ok!(SubNFTModule::mint_fungible(alice.clone(), alice_address, sub_collection_id, half));
+        assert_ok!(SubNFTModule::mint_fungible(alice.clone(), bob_address, sub_collection_id, half));
+
+        assert_noop!(
+            SubNFTModule::vote_reserve_price(alice.clone(), sub_collection_id, u64::MAX),
+            Error::<Test>::NumOverflow
+        );
+        assert_ok!(SubNFTModule::vote_reserve_price(alice.clone(), sub_collection_id, 100));
+        assert_ok!(SubNFTModule::vote_reserve_price(bob, sub_collection_id, 100));
+        assert_eq!(SubNFTModule::reserve_price(&sub_collection_id), Some(100));
+
+        assert_ok!(SubNFTModule::start_buyout(carol.clone(), sub_collection_id, 100));
+        System::set_block_number(11);
+        assert_ok!(SubNFTModule::settle_buyout(carol, sub_collection_id));
+
+        assert_ok!(SubNFTModule::redeem(alice, sub_collection_id));
+        assert_eq!(Balances::free_balance(alice_address), 1050);
+    });
+}
+
+#[test]
+fn buyout_failed() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        let bob_address = 2;
+        let bob = Origin::signed(bob_address);
+        let carol = Origin::signed(3);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 1).unwrap();
+
+        assert_ok!(SubNFTModule::create(alice.clone(), collection_id, 0, true));
+        let nonce = CollectionModule::get_nonce();
+        let sub_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        assert_ok!(SubNFTModule::mint_fungible(alice.clone(), alice_address, sub_collection_id, 40));
+        assert_ok!(SubNFTModule::mint_fungible(alice.clone(), bob_address, sub_collection_id, 60));
+
+        assert_noop!(
+            SubNFTModule::start_buyout(carol.clone(), sub_collection_id, 100),
+            Error::<Test>::NoReservePrice
+        );
+        assert_noop!(
+            SubNFTModule::vote_reserve_price(carol.clone(), sub_collection_id, 100),
+            Error::<Test>::PermissionDenied
+        );
+
+        // less than half of the supply voted
+        assert_ok!(SubNFTModule::vote_reserve_price(alice.clone(), sub_collection_id, 100));
+        assert_noop!(
+            SubNFTModule::start_buyout(carol.clone(), sub_collection_id, 100),
+            Error::<Test>::NoReservePrice
+        );
+
+        assert_ok!(SubNFTModule::vote_reserve_price(bob.clone(), sub_collection_id, 100));
+        assert_noop!(
+            SubNFTModule::start_buyout(carol.clone(), sub_collection_id, 99),
+            Error::<Test>::BidTooLow
+        );
+        assert_noop!(
+            SubNFTModule::bid_buyout(carol.clone(), sub_collection_id, 100),
+            Error::<Test>::BuyoutNotFound
+        );
+
+        assert_ok!(SubNFTModule::start_buyout(carol.clone(), sub_collection_id, 100));
+        assert_noop!(
+            SubNFTModule::start_buyout(bob.clone(), sub_collection_id, 200),
+            Error::<Test>::BuyoutInProgress
+        );
+        assert_noop!(
+            SubNFTModule::bid_buyout(bob.clone(), sub_collection_id, 100),
+            Error::<Test>::BidTooLow
+        );
+        assert_noop!(
+            SubNFTModule::recover(alice.clone(), sub_collection_id),
+            Error::<Test>::BuyoutInProgress
+        );
+        assert_noop!(
+            SubNFTModule::settle_buyout(carol.clone(), sub_collection_id),
+            Error::<Test>::AuctionNotEnded
+        );
+        assert_noop!(
+            SubNFTModule::redeem(alice, sub_collection_id),
+            Error::<Test>::NotBoughtOut
+        );
+
+        System::set_block_number(11);
+        assert_noop!(
+            SubNFTModule::bid_buyout(bob, sub_collection_id, 200),
+            Error::<Test>::AuctionEnded
+        );
+    });
+}
+
+#[test]
+fn basket_success() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        System::set_block_number(1);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 3).unwrap();
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let other_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, other_collection_id, vec![2, 3, 3], 1).unwrap();
+
+        assert_ok!(SubNFTModule::create_basket(alice.clone(), vec![2, 3, 3], vec![(collection_id, 0), (other_collection_id, 0)]));
+        let nonce = CollectionModule::get_nonce();
+        let sub_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        assert_eq!(NFTModule::tokens(collection_id, 0).owner, SubNFTModule::account_id());
+        assert_eq!(NFTModule::tokens(other_collection_id, 0).owner, SubNFTModule::account_id());
+        assert!(System::events().iter().any(|record| record.event == Event::pallet_sub(crate::RawEvent::BasketCreated(
+            sub_collection_id,
+            alice_address,
+            vec![(collection_id, 0), (other_collection_id, 0)],
+            TokenType::Fungible,
+        ))));
+
+        assert_ok!(SubNFTModule::add_to_basket(alice.clone(), sub_collection_id, vec![(collection_id, 1)]));
+        assert_eq!(
+            SubNFTModule::basket(sub_collection_id),
+            Some(vec![(collection_id, 0), (other_collection_id, 0), (collection_id, 1)])
+        );
+
+        assert_ok!(SubNFTModule::mint_fungible(alice.clone(), alice_address, sub_collection_id, 100));
+        assert_ok!(SubNFTModule::recover(alice.clone(), sub_collection_id));
+
+        assert_eq!(NFTModule::tokens(collection_id, 0).owner, alice_address);
+        assert_eq!(NFTModule::tokens(other_collection_id, 0).owner, alice_address);
+        assert_eq!(NFTModule::address_balances((collection_id, alice_address)), 3);
+        assert_eq!(SubNFTModule::basket(sub_collection_id), None);
+    });
+}
+
+#[test]
+fn basket_failed() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(2);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 5).unwrap();
+
+        assert_noop!(
+            SubNFTModule::create_basket(alice.clone(), vec![2, 3, 3], vec![]),
+            Error::<Test>::EmptyBasket
+        );
+        assert_noop!(
+            SubNFTModule::create_basket(alice.clone(), vec![2, 3, 3], vec![(collection_id, 0), (collection_id, 1), (collection_id, 2), (collection_id, 3)]),
+            Error::<Test>::BasketTooLarge
+        );
+        assert_noop!(
+            SubNFTModule::create_basket(bob.clone(), vec![2, 3, 3], vec![(collection_id, 0)]),
+            pallet_nft::Error::<Test>::PermissionDenied
+        );
+
+        assert_ok!(SubNFTModule::create_basket(alice.clone(), vec![2, 3, 3], vec![(collection_id, 0), (collection_id, 1)]));
+        let nonce = CollectionModule::get_nonce();
+        let sub_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+
+        assert_noop!(
+            SubNFTModule::add_to_basket(bob, sub_collection_id, vec![(collection_id, 2)]),
+            Error::<Test>::PermissionDenied
+        );
+        assert_noop!(
+            SubNFTModule::add_to_basket(alice.clone(), sub_collection_id, vec![(collection_id, 2), (collection_id, 3)]),
+            Error::<Test>::BasketTooLarge
+        );
+
+        assert_ok!(SubNFTModule::mint_fungible(alice.clone(), alice_address, sub_collection_id, 100));
+        assert_noop!(
+            SubNFTModule::add_to_basket(alice, sub_collection_id, vec![(collection_id, 2)]),
+            Error::<Test>::AlreadyMinted
+        );
+    });
+}
+
+#[test]
+fn create_with_fixed_supply_success() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 1).unwrap();
+
+        assert_ok!(SubNFTModule::create_with_fixed_supply(alice.clone(), collection_id, 0, 100));
+        let nonce = CollectionModule::get_nonce();
+        let sub_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+
+        assert_eq!(NFTModule::tokens(collection_id, 0).owner, SubNFTModule::account_id());
+        assert_eq!(NFTModule::address_balances((sub_collection_id, alice_address)), 100);
+        assert_eq!(CollectionModule::collections(sub_collection_id).total_supply, 100);
+        assert!(crate::IssuanceClosed::<Test>::contains_key(sub_collection_id));
+
+        assert_ok!(SubNFTModule::recover(alice, sub_collection_id));
+        assert_eq!(NFTModule::tokens(collection_id, 0).owner, alice_address);
+        assert!(!crate::IssuanceClosed::<Test>::contains_key(sub_collection_id));
+    });
+}
+
+#[test]
+fn create_with_fixed_supply_failed() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(2);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 1).unwrap();
+
+        assert_noop!(
+            SubNFTModule::create_with_fixed_supply(bob, collection_id, 0, 100),
+            pallet_nft::Error::<Test>::PermissionDenied
+        );
+        assert_noop!(
+            SubNFTModule::create_with_fixed_supply(alice.clone(), collection_id, 0, 0),
+            pallet_nft::Error::<Test>::AmountLessThanOne
+        );
+
+        assert_ok!(SubNFTModule::create_with_fixed_supply(alice.clone(), collection_id, 0, 100));
+        let nonce = CollectionModule::get_nonce();
+        let sub_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+
+        assert_noop!(
+            SubNFTModule::mint_fungible(alice.clone(), alice_address, sub_collection_id, 100),
+            Error::<Test>::IssuanceClosed
+        );
+        assert_noop!(
+            SubNFTModule::mint_non_fungible(alice, alice_address, sub_collection_id, vec![2, 3, 3], 1),
+            Error::<Test>::IssuanceClosed
+        );
+    });
+}
+
+#[test]
+fn revenue_success() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let charlie_address = 3;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(bob_address);
+        let charlie = Origin::signed(charlie_address);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 1).unwrap();
+
+        assert_ok!(SubNFTModule::create_with_fixed_supply(alice.clone(), collection_id, 0, 100));
+        let nonce = CollectionModule::get_nonce();
+        let sub_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        assert_ok!(NFTModule::transfer_fungible(alice.clone(), bob_address, sub_collection_id, 25));
+
+        assert_ok!(SubNFTModule::deposit_revenue(charlie.clone(), sub_collection_id, RevenueAsset::Native, 200));
+        assert_eq!(Balances::free_balance(charlie_address), 800);
+
+        // tokens bought after the deposit don't earn it
+        assert_ok!(NFTModule::transfer_fungible(alice.clone(), charlie_address, sub_collection_id, 25));
+        assert_noop!(
+            SubNFTModule::claim_revenue(charlie.clone(), sub_collection_id, RevenueAsset::Native),
+            Error::<Test>::NoRevenue
+        );
+
+        assert_ok!(SubNFTModule::claim_revenue(alice.clone(), sub_collection_id, RevenueAsset::Native));
+        assert_ok!(SubNFTModule::claim_revenue(bob.clone(), sub_collection_id, RevenueAsset::Native));
+        assert_eq!(Balances::free_balance(alice_address), 1150);
+        assert_eq!(Balances::free_balance(bob_address), 1050);
+
+        assert_ok!(SubNFTModule::deposit_revenue(bob.clone(), sub_collection_id, RevenueAsset::Native, 100));
+        assert_ok!(SubNFTModule::claim_revenue(charlie, sub_collection_id, RevenueAsset::Native));
+        assert_eq!(Balances::free_balance(charlie_address), 825);
+
+        // FT revenue
+        CollectionModule::create_collection(bob.clone(), vec![2, 3, 3], true).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let ft_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_fungible(bob.clone(), bob_address, ft_collection_id, 1000).unwrap();
+        assert_ok!(SubNFTModule::deposit_revenue(bob.clone(), sub_collection_id, RevenueAsset::Fungible(ft_collection_id), 1000));
+        assert_eq!(SubNFTModule::revenue_assets(sub_collection_id), vec![RevenueAsset::Native, RevenueAsset::Fungible(ft_collection_id)]);
+
+        assert_ok!(SubNFTModule::claim_revenue(bob, sub_collection_id, RevenueAsset::Fungible(ft_collection_id)));
+        assert_eq!(NFTModule::address_balances((ft_collection_id, bob_address)), 250);
+    });
+}
+
+#[test]
+fn revenue_skips_pallet_accounts() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let charlie_address = 3;
+        let alice = Origin::signed(alice_address);
+        let charlie = Origin::signed(charlie_address);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 2).unwrap();
+
+        assert_ok!(SubNFTModule::create_with_fixed_supply(alice.clone(), collection_id, 0, 100));
+        let nonce = CollectionModule::get_nonce();
+        let sub_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+
+        // half of the SubTokens are held by the graph account
+        assert_ok!(GraphModule::link_fungible(alice.clone(), None, None, sub_collection_id, collection_id, 1, 50));
+        assert_ok!(SubNFTModule::deposit_revenue(charlie, sub_collection_id, RevenueAsset::Native, 100));
+
+        assert_ok!(GraphModule::recover_fungible(alice.clone(), collection_id, 1, sub_collection_id, 50));
+        assert_ok!(SubNFTModule::claim_revenue(alice, sub_collection_id, RevenueAsset::Native));
+        assert_eq!(Balances::free_balance(alice_address), 1100);
+        assert_eq!(Balances::free_balance(SubNFTModule::account_id()), 0);
+    });
+}
+
+#[test]
+fn revenue_failed() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(2);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 1).unwrap();
+
+        assert_noop!(
+            SubNFTModule::deposit_revenue(alice.clone(), collection_id, RevenueAsset::Native, 100),
+            Error::<Test>::SubTokenNotFound
+        );
+
+        assert_ok!(SubNFTModule::create(alice.clone(), collection_id, 0, true));
+        let nonce = CollectionModule::get_nonce();
+        let sub_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+
+        assert_noop!(
+            SubNFTModule::deposit_revenue(alice.clone(), sub_collection_id, RevenueAsset::Native, 100),
+            Error::<Test>::NoHolders
+        );
+
+        assert_ok!(SubNFTModule::mint_fungible(alice.clone(), alice_address, sub_collection_id, 100));
+        assert_noop!(
+            SubNFTModule::deposit_revenue(alice.clone(), sub_collection_id, RevenueAsset::Native, 0),
+            Error::<Test>::NoRevenue
+        );
+        assert_noop!(
+            SubNFTModule::deposit_revenue(alice.clone(), sub_collection_id, RevenueAsset::Fungible(sub_collection_id), 10),
+            Error::<Test>::WrongRevenueAsset
+        );
+        assert_noop!(
+            SubNFTModule::claim_revenue(bob, sub_collection_id, RevenueAsset::Native),
+            Error::<Test>::NoRevenue
+        );
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], true).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let ft_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_fungible(alice.clone(), alice_address, ft_collection_id, 100).unwrap();
+
+        assert_ok!(SubNFTModule::deposit_revenue(alice.clone(), sub_collection_id, RevenueAsset::Native, 100));
+        assert_ok!(SubNFTModule::deposit_revenue(alice.clone(), sub_collection_id, RevenueAsset::Fungible(ft_collection_id), 100));
+        assert_noop!(
+            SubNFTModule::deposit_revenue(alice, sub_collection_id, RevenueAsset::Fungible(H256::repeat_byte(1)), 10),
+            Error::<Test>::TooManyRevenueAssets
+        );
+    });
+}
+
+#[test]
+fn release_success() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let charlie_address = 3;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(bob_address);
+        let charlie = Origin::signed(charlie_address);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 1).unwrap();
+
+        assert_ok!(SubNFTModule::create_with_fixed_supply(alice.clone(), collection_id, 0, 100));
+        let nonce = CollectionModule::get_nonce();
+        let sub_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        assert_ok!(NFTModule::transfer_fungible(alice.clone(), bob_address, sub_collection_id, 50));
+        assert_ok!(NFTModule::transfer_fungible(alice.clone(), charlie_address, sub_collection_id, 20));
+
+        assert_ok!(SubNFTModule::propose_release(bob.clone(), sub_collection_id, 500));
+        assert_eq!(Balances::reserved_balance(bob_address), 500);
+
+        assert_ok!(SubNFTModule::approve_release(bob.clone(), sub_collection_id));
+        assert_eq!(SubNFTModule::release(sub_collection_id).unwrap().approved, 50);
+
+        // approvals follow the balances
+        assert_ok!(NFTModule::transfer_fungible(alice.clone(), bob_address, sub_collection_id, 10));
+        assert_eq!(SubNFTModule::release(sub_collection_id).unwrap().approved, 60);
+
+        assert_ok!(SubNFTModule::approve_release(alice.clone(), sub_collection_id));
+        assert_eq!(NFTModule::tokens(collection_id, 0).owner, bob_address);
+        assert_eq!(Balances::reserved_balance(bob_address), 0);
+        assert_eq!(Balances::free_balance(bob_address), 500);
+        assert_eq!(SubNFTModule::release(sub_collection_id), None);
+        assert_eq!(SubNFTModule::buyout_proceeds(sub_collection_id), Some((500, 100)));
+
+        // the dissenter is compensated
+        assert_ok!(SubNFTModule::redeem(charlie, sub_collection_id));
+        assert_eq!(Balances::free_balance(charlie_address), 1100);
+    });
+}
+
+#[test]
+fn release_failed() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(bob_address);
+        let charlie = Origin::signed(3);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 1).unwrap();
+
+        assert_noop!(
+            SubNFTModule::propose_release(bob.clone(), collection_id, 500),
+            Error::<Test>::SubTokenNotFound
+        );
+
+        assert_ok!(SubNFTModule::create_with_fixed_supply(alice.clone(), collection_id, 0, 100));
+        let nonce = CollectionModule::get_nonce();
+        let sub_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+
+        assert_noop!(
+            SubNFTModule::approve_release(alice.clone(), sub_collection_id),
+            Error::<Test>::ReleaseNotFound
+        );
+        assert_noop!(
+            SubNFTModule::propose_release(bob.clone(), sub_collection_id, 2000),
+            pallet_balances::Error::<Test, _>::InsufficientBalance
+        );
+
+        assert_ok!(SubNFTModule::propose_release(bob.clone(), sub_collection_id, 500));
+        assert_noop!(
+            SubNFTModule::propose_release(charlie.clone(), sub_collection_id, 500),
+            Error::<Test>::ReleaseInProgress
+        );
+        assert_noop!(
+            SubNFTModule::approve_release(charlie.clone(), sub_collection_id),
+            Error::<Test>::PermissionDenied
+        );
+        assert_noop!(
+            SubNFTModule::cancel_release(charlie, sub_collection_id),
+            Error::<Test>::PermissionDenied
+        );
+
+        assert_ok!(NFTModule::transfer_fungible(alice.clone(), bob_address, sub_collection_id, 50));
+        assert_ok!(SubNFTModule::approve_release(alice.clone(), sub_collection_id));
+        assert_noop!(
+            SubNFTModule::approve_release(alice, sub_collection_id),
+            Error::<Test>::AlreadyApproved
+        );
+
+        assert_ok!(SubNFTModule::cancel_release(bob, sub_collection_id));
+        assert_eq!(Balances::reserved_balance(bob_address), 0);
+        assert_eq!(SubNFTModule::release(sub_collection_id), None);
+    });
+}
+
+#[test]
+fn provenance_success() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+        System::set_block_number(1);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 1).unwrap();
+
+        assert_ok!(SubNFTModule::create(alice.clone(), collection_id, 0, false));
+        let nonce = CollectionModule::get_nonce();
+        let sub_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        assert!(System::events().iter().any(|record| record.event == Event::pallet_sub(crate::RawEvent::SubCollectionCreated(
+            sub_collection_id,
+            alice_address,
+            collection_id,
+            0,
+            TokenType::NonFungible,
+        ))));
+
+        // fractionalize a SubNFT again
+        assert_ok!(SubNFTModule::mint_non_fungible(alice.clone(), alice_address, sub_collection_id, vec![2, 3, 3], 1));
+        assert_ok!(SubNFTModule::create(alice.clone(), sub_collection_id, 0, true));
+        let nonce = CollectionModule::get_nonce();
+        let sub_sub_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+
+        let provenance = SubNFTModule::provenance(sub_sub_collection_id);
+        assert_eq!(provenance.len(), 2);
+        assert_eq!(provenance[0].0, sub_sub_collection_id);
+        assert_eq!(provenance[0].1.parents, vec![(sub_collection_id, 0)]);
+        assert_eq!(provenance[0].1.token_type, TokenType::Fungible);
+        assert_eq!(provenance[1].0, sub_collection_id);
+        assert_eq!(provenance[1].1.parents, vec![(collection_id, 0)]);
+        assert_eq!(provenance[1].1.creator, alice_address);
+
+        // recovered SubToken collections stay in the history
+        System::set_block_number(2);
+        assert_ok!(SubNFTModule::recover(alice.clone(), sub_sub_collection_id));
+        assert_eq!(SubNFTModule::sub_token_origin(sub_sub_collection_id).unwrap().ended_at, Some(2));
+
+        assert_ok!(SubNFTModule::create(alice.clone(), sub_collection_id, 0, false));
+        let nonce = CollectionModule::get_nonce();
+        let next_sub_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        assert_eq!(
+            SubNFTModule::sub_collections_of((sub_collection_id, 0)),
+            vec![sub_sub_collection_id, next_sub_collection_id]
+        );
+        assert_eq!(SubNFTModule::sub_token_origin(next_sub_collection_id).unwrap().ended_at, None);
+
+        // only the last `MaxSubCollections` are indexed
+        assert_ok!(SubNFTModule::recover(alice.clone(), next_sub_collection_id));
+        assert_ok!(SubNFTModule::create(alice.clone(), sub_collection_id, 0, false));
+        let nonce = CollectionModule::get_nonce();
+        let last_sub_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        assert_eq!(
+            SubNFTModule::sub_collections_of((sub_collection_id, 0)),
+            vec![next_sub_collection_id, last_sub_collection_id]
+        );
+        assert!(SubNFTModule::sub_token_origin(sub_sub_collection_id).is_some());
+    });
+}
+
+#[test]
+fn release_replaced_by_higher_price() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let bob_address = 2;
+        let charlie_address = 3;
+        let alice = Origin::signed(alice_address);
+        let bob = Origin::signed(bob_address);
+        let charlie = Origin::signed(charlie_address);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 1).unwrap();
+
+        assert_ok!(SubNFTModule::create_with_fixed_supply(alice.clone(), collection_id, 0, 100));
+        let nonce = CollectionModule::get_nonce();
+        let sub_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        assert_ok!(NFTModule::transfer_fungible(alice.clone(), bob_address, sub_collection_id, 50));
+
+        // a proposal at price 0 doesn't block others
+        assert_ok!(SubNFTModule::propose_release(bob.clone(), sub_collection_id, 0));
+        assert_ok!(SubNFTModule::approve_release(bob, sub_collection_id));
+
+        assert_ok!(SubNFTModule::propose_release(charlie.clone(), sub_collection_id, 300));
+        let release = SubNFTModule::release(sub_collection_id).unwrap();
+        assert_eq!(release.recipient, charlie_address);
+        assert_eq!(release.approved, 50);
+        assert!(crate::ReleaseApprovals::<Test>::contains_key(sub_collection_id, bob_address));
+        assert_eq!(Balances::reserved_balance(charlie_address), 300);
+        assert_noop!(
+            SubNFTModule::propose_release(charlie, sub_collection_id, 300),
+            Error::<Test>::ReleaseInProgress
+        );
+
+        // the approvals carry over to the higher price
+        assert_ok!(SubNFTModule::approve_release(alice, sub_collection_id));
+        assert_eq!(NFTModule::tokens(collection_id, 0).owner, charlie_address);
+        assert_eq!(Balances::reserved_balance(charlie_address), 0);
+    });
+}
+
+#[test]
+fn graph_root_success() {
+    new_test_ext().execute_with(|| {
+        let alice_address = 1;
+        let alice = Origin::signed(alice_address);
+
+        CollectionModule::create_collection(alice.clone(), vec![2, 3, 3], false).unwrap();
+        let nonce = CollectionModule::get_nonce();
+        let collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        NFTModule::mint_non_fungible(alice.clone(), alice_address, collection_id, vec![2, 3, 3], 3).unwrap();
+
+        // 0 <- 1 <- 2
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), collection_id, 1, collection_id, 0));
+        assert_ok!(GraphModule::link_non_fungible(alice.clone(), collection_id, 2, collection_id, 1));
+
+        assert_noop!(
+            SubNFTModule::create(alice.clone(), collection_id, 1, true),
+            Error::<Test>::TokenLinked
+        );
+
+        assert_ok!(SubNFTModule::create(alice.clone(), collection_id, 0, true));
+        let nonce = CollectionModule::get_nonce();
+        let sub_collection_id = CollectionModule::generate_collection_id(nonce).unwrap();
+        assert_eq!(SubNFTModule::locked_subtree(&sub_collection_id), vec![(collection_id, 1), (collection_id, 2)]);
+
+        // children can't be taken out of the vault
+        assert_noop!(
+            GraphModule::recover_non_fungible(alice.clone(), collection_id, 2),
+            pallet_graph::Error::<Test>::PermissionDenied
+        );
+
+        assert_ok!(SubNFTModule::recover(alice.clone(), sub_collection_id));
+        assert_eq!(NFTModule::tokens(collection_id, 0).owner, alice_address);
+        assert_eq!(GraphModule::child_to_parent((collection_id, 2)), (collection_id, 1));
+
+        assert_ok!(GraphModule::recover_non_fungible(alice, collection_id, 2));
+        assert_eq!(NFTModule::tokens(collection_id, 2).owner, alice_address);
+    });
+}
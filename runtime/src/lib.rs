@@ -101,10 +101,10 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("node-template"),
 	impl_name: create_runtime_str!("node-template"),
 	authoring_version: 1,
-	spec_version: 100,
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	transaction_version: 2,
 };
 
 
@@ -264,9 +264,25 @@ impl pallet_sudo::Config for Runtime {
 	type Call = Call;
 }
 
+impl pallet_utility::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type WeightInfo = pallet_utility::weights::SubstrateWeight<Runtime>;
+}
+
+parameter_types! {
+	pub const CollectionDeposit: Balance = 1_000;
+	pub const StringLimit: u32 = 50;
+	pub const MaxBatchTransfers: u32 = 100;
+	pub const MaxTokenLocks: u32 = 100;
+}
+
 impl pallet_collection::Config for Runtime {
 	type Event = Event;
 	type RandomnessSource = RandomnessCollectiveFlip;
+	type Currency = Balances;
+	type CollectionDeposit = CollectionDeposit;
+	type StringLimit = StringLimit;
 }
 
 impl pallet_exchange::Config for Runtime {
@@ -274,23 +290,61 @@ impl pallet_exchange::Config for Runtime {
 	type Currency = Balances;
 	type Collection = CollectionModule;
 	type NFT = NFTModule;
+	type Graph = GraphModule;
 }
 
 impl pallet_nft::Config for Runtime {
 	type Collection = CollectionModule;
 	type Event = Event;
+	type OnTransfer = SubNFTModule;
+	type OnMint = SubNFTModule;
+	type OnBurn = (GraphModule, SubNFTModule);
+	type MaxBatchTransfers = MaxBatchTransfers;
+	type Currency = Balances;
+	type Signature = Signature;
+	type Signer = <Signature as Verify>::Signer;
+	type RangeKeepers = (ExchangeModule, GraphModule, SubNFTModule, NFTDAOModule);
+	type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+	type MaxTokenLocks = MaxTokenLocks;
+}
+
+parameter_types! {
+	pub const BuyoutPeriod: BlockNumber = 3 * DAYS;
+	pub const MaxBasketSize: u32 = 50;
+	pub const MaxRevenueAssets: u32 = 10;
+	pub const MaxSubCollections: u32 = 20;
+	pub const ReleaseThreshold: Perbill = Perbill::from_percent(67);
+	pub SubTokenPalletAccounts: Vec<AccountId> = vec![ExchangeModule::account_id(), GraphModule::account_id()];
 }
 
 impl pallet_sub::Config for Runtime {
 	type Event = Event;
 	type Collection = CollectionModule;
 	type NFT = NFTModule;
+	type Graph = GraphModule;
+	type Currency = Balances;
+	type BuyoutPeriod = BuyoutPeriod;
+	type MaxBasketSize = MaxBasketSize;
+	type MaxRevenueAssets = MaxRevenueAssets;
+	type MaxSubCollections = MaxSubCollections;
+	type ReleaseThreshold = ReleaseThreshold;
+	type PalletAccounts = SubTokenPalletAccounts;
+}
+
+parameter_types! {
+	pub const MaxEquippables: u32 = 20;
+	pub const MaxDepth: u32 = 20;
+	pub const MaxDescendants: u32 = 200;
 }
 
 impl pallet_graph::Config for Runtime {
 	type Event = Event;
 	type Collection = CollectionModule;
 	type NFT = NFTModule;
+	type StringLimit = StringLimit;
+	type MaxEquippables = MaxEquippables;
+	type MaxDepth = MaxDepth;
+	type MaxDescendants = MaxDescendants;
 }
 
 impl pallet_nftdao::Config for Runtime {
@@ -299,6 +353,7 @@ impl pallet_nftdao::Config for Runtime {
 	type RandomnessSource = RandomnessCollectiveFlip;
 	type Currency = Balances;
 	type NFT = NFTModule;
+	type Graph = GraphModule;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -317,12 +372,16 @@ construct_runtime!(
 		TransactionPayment: pallet_transaction_payment::{Module, Storage},
 		Sudo: pallet_sudo::{Module, Call, Config<T>, Storage, Event<T>},
 		// Include the custom logic from the template pallet in the runtime.
-		ExchangeModule: pallet_exchange::{Module, Call, Storage, Event<T>},
-		CollectionModule: pallet_collection::{Module, Call, Storage, Event<T>},
-		NFTModule: pallet_nft::{Module, Call, Storage, Event<T>},
-		GraphModule: pallet_graph::{Module, Call, Storage, Event<T>},
-		SubNFTModule: pallet_sub::{Module, Call, Storage, Event<T>},
-		NFTDAOModule: pallet_nftdao::{Module, Call, Storage, Event<T>},
+		// Genesis is built in declaration order, so collections and tokens come before exchange
+		// orders. The explicit indices keep the call and event indices of the live chain.
+		CollectionModule: pallet_collection::{Module, Call, Storage, Config<T>, Event<T>} = 9,
+		NFTModule: pallet_nft::{Module, Call, Storage, Config<T>, Event<T>} = 10,
+		ExchangeModule: pallet_exchange::{Module, Call, Storage, Config<T>, Event<T>} = 8,
+		GraphModule: pallet_graph::{Module, Call, Storage, Event<T>} = 11,
+		SubNFTModule: pallet_sub::{Module, Call, Storage, Event<T>} = 12,
+		NFTDAOModule: pallet_nftdao::{Module, Call, Storage, Config<T>, Event<T>} = 13,
+		// Added after the custom pallets so that their indices don't change.
+		Utility: pallet_utility::{Module, Call, Event} = 14,
 	}
 );
 
@@ -475,6 +534,40 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_collection_runtime_api::CollectionApi<Block, AccountId, Hash> for Runtime {
+		fn next_collection_id(who: AccountId, salt: Option<Vec<u8>>) -> Hash {
+			CollectionModule::next_collection_id(&who, salt)
+		}
+
+		fn fungible_metadata(collection_id: Hash) -> Option<pallet_collection::FungibleMetadata> {
+			CollectionModule::fungible_metadata(collection_id)
+		}
+	}
+
+	impl pallet_sub_runtime_api::SubApi<Block, AccountId, Hash, BlockNumber> for Runtime {
+		fn sub_token_origin(sub_token_collection_id: Hash) -> Option<pallet_sub::SubTokenOrigin<AccountId, Hash, BlockNumber>> {
+			SubNFTModule::sub_token_origin(sub_token_collection_id)
+		}
+
+		fn sub_collections_of(collection_id: Hash, start_idx: u128) -> Vec<Hash> {
+			SubNFTModule::sub_collections_of((collection_id, start_idx))
+		}
+
+		fn locked_subtree(sub_token_collection_id: Hash) -> Vec<(Hash, u128)> {
+			SubNFTModule::locked_subtree(&sub_token_collection_id)
+		}
+
+		fn provenance(collection_id: Hash) -> Vec<(Hash, pallet_sub::SubTokenOrigin<AccountId, Hash, BlockNumber>)> {
+			SubNFTModule::provenance(collection_id)
+		}
+	}
+
+	impl pallet_graph_runtime_api::GraphApi<Block, Hash> for Runtime {
+		fn subtree(collection_id: Hash, token_id: u128) -> Option<pallet_graph::Subtree<Hash>> {
+			GraphModule::subtree(collection_id, token_id).ok()
+		}
+	}
+
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance>
 		for Runtime {
 		fn query_info(